@@ -0,0 +1,54 @@
+//! Positions for the benchmarks, taken from games of the random bot so they look like real ones.
+//! They are written down instead of played again, so the benchmarks keep measuring the same
+//! positions when the rules or the bot change.
+
+//every benchmark only uses some of them
+#![allow(dead_code)]
+
+use hive_bevy::game_state::GameState;
+use hive_bevy::notation::play_moves;
+use hive_bevy::rule_set::RuleSet;
+
+/// Games with the default rules and 12 tiles in play.
+pub const MIDGAMES: [&str; 8] = [
+    r"wS1;bA1 wS1-;wG1 -wS1;bS1 bA1-;wQ -wG1;bQ bS1-;wP \wS1;bB1 \bQ;wB1 -wP;bA2 -bB1;wG2 -wB1;bA3 bQ-",
+    r"wG1;bS1 -wG1;wS1 wG1-;bG1 -bS1;wQ wS1-;bQ -bG1;wB1 \wQ;bP \bS1;wA1 -wB1;bB1 -bP;wP \wA1;bG1 -wP;wA1 /wP;bS1 /bP;wB2 /wQ",
+    r"wG1;bA1 \wG1;wS1 /wG1;bQ bA1/;wQ wG1-;bQ \bA1;wQ wG1/;bP bQ/;wG2 wS1-;bB1 -bQ;wA1 wG2\;bA2 -bP;wS2 /wS1;bG1 \bA2",
+    r"wS1;bG1 /wS1;wQ wS1/;bS1 -bG1;wQ \wS1;bQ bG1\;wG1 wS1-;bQ /bG1;wA1 \wQ;bP /bS1;wA2 wA1/;bB1 \bS1;wA2 \bB1;bQ -bP;wA1 wQ/;bQ /bG1;wS2 \wA2;bG2 /bP",
+    r"wG1;bG1 wG1\;wP -wG1;bP /bG1;wQ -wP;bQ -bP;wG2 \wQ;bG2 bG1-;wG3 wG1/;bA1 bP\;wA1 /wQ;bG3 /bG2",
+    r"wG1;bP \wG1;wP /wG1;bG1 -bP;wQ -wP;bQ \bG1;wG2 wG1-;bG2 \bP;wA1 wP\;bG2 \wG2;wG3 /wQ;bG2 /bP;wA2 /wP;bS1 bP/",
+    r"wP;bQ \wP;wB1 wP-;bP bQ/;wQ /wP;bG1 bP-;wB2 wQ\;bA1 -bP;wB1 wP;bG2 /bA1;wB2 /wQ;bA1 bG1\;wB2 -wQ;bA2 bG1/;wA1 wQ\;bA1 /wQ;wS1 wA1/;bA1 wA1-;wA1 \bP;bA2 bA1/;wA1 bA2/;bA3 /bA1",
+    r"wS1;bP wS1\;wG1 -wS1;bB1 bP-;wQ -wG1;bQ /bP;wG2 \wS1;bB2 bQ\;wQ -wG2;bB1 bP;wP wG2/;bB2 /bQ;wB1 /wG1;bB2 -bQ;wA1 -wP;bG1 bQ-;wA1 -wB1;bA1 bG1-",
+];
+
+/// Games with the default rules and 22 tiles in play.
+pub const ENDGAMES: [&str; 8] = [
+    r"wS1;bA1 wS1-;wG1 -wS1;bS1 bA1-;wQ -wG1;bQ bS1-;wP \wS1;bB1 \bQ;wB1 -wP;bA2 -bB1;wG2 -wB1;bA3 bQ-;wG1 \wP;bB1 bS1;wG1 wP/;bA2 \bQ;wS2 \wG2;bP \bA3;wB2 /wS1;bS2 \bA2;wQ /wP;bB2 bS2/;wA1 -wG1;bQ bP/;wG3 \wA1;bQ bA3/;wA2 /wQ;bA3 \wG1;wA2 /bQ;bA3 -wS2;wA2 /wB1;bS3 \bP;wA2 /bA3;bB1 /bS1;wQ /wB1;bG1 bQ/",
+    r"wG1;bS1 -wG1;wS1 wG1-;bG1 -bS1;wQ wS1-;bQ -bG1;wB1 \wQ;bP \bS1;wA1 -wB1;bB1 -bP;wP \wA1;bG1 -wP;wA1 /wP;bS1 /bP;wB2 /wQ;bA1 \bB1;wA2 /wB2;bB2 bA1/;wA3 -wA2;bS2 /bS1;wS2 /wA2;bS1 /bS2;wA4 /wA3;bA2 /bP;wA4 bS1-;bG2 /bQ;wA3 bB2/;bS3 -bG1",
+    r"wG1;bA1 \wG1;wS1 /wG1;bQ bA1/;wQ wG1-;bQ \bA1;wQ wG1/;bP bQ/;wG2 wS1-;bB1 -bQ;wA1 wG2\;bA2 -bP;wS2 /wS1;bG1 \bA2;wA1 -bB1;bG2 \bG1;wB1 wQ-;bA3 \bP;wS3 wB1\;bG3 /bG2;wA2 \wB1;bA3 \bG2;wA2 wS2\;bS1 -bG3;wG3 /wA1;bS2 /bS1;wP wG3\",
+    r"wS1;bG1 /wS1;wQ wS1/;bS1 -bG1;wQ \wS1;bQ bG1\;wG1 wS1-;bQ /bG1;wA1 \wQ;bP /bS1;wA2 wA1/;bB1 \bS1;wA2 \bB1;bQ -bP;wA1 wQ/;bQ /bG1;wS2 \wA2;bG2 /bP;wB1 wG1\;bS2 /bG2;wP \wS2;bA1 /bQ;wS3 \wG1;bA1 -wP;wB2 wS2/;bA2 -bP;wB2 wP;bG3 -bA2;wG2 -wA1;bA1 wG1/;wG3 /wS2;bB2 bA1-",
+    r"wG1;bG1 wG1\;wP -wG1;bP /bG1;wQ -wP;bQ -bP;wG2 \wQ;bG2 bG1-;wG3 wG1/;bA1 bP\;wA1 /wQ;bG3 /bG2;wS1 -wA1;bA2 /bP;wB1 \wS1;bS1 /bQ;wB2 wG3-;bS1 bA1\;wA2 /wA1;bA2 bG3-;wA2 /bQ;bG3 /bA1;wA2 /bS1;bA3 bA2\;wB1 wS1;bB1 /bP;wA2 /wA1;bS2 /bA3;wB1 /wG2;bA2 -wG3;wA3 wB2-",
+    r"wG1;bP \wG1;wP /wG1;bG1 -bP;wQ -wP;bQ \bG1;wG2 wG1-;bG2 \bP;wA1 wP\;bG2 \wG2;wG3 /wQ;bG2 /bP;wA2 /wP;bS1 bP/;wA2 wA1\;bG2 /wG3;wS1 wA2/;bQ -bS1;wA2 wS1\;bA1 -bG2;wB1 -wQ;bS1 /bP;wA2 wG2-;bA2 \bQ;wB2 /wG2;bA2 /bA1;wS2 wA2-;bA2 bQ/;wA1 -bA1;bS2 bG2\;wG1 wS2-;bA2 \wG2;wS3 /wS1;bA2 wS3\;wA3 \wS2;bA2 /wB1;wA3 -bG1;bA2 -wB1;wA1 wG1\;bA1 -wS3;wA1 -bG2;bA3 /bA1",
+    r"wP;bQ \wP;wB1 wP-;bP bQ/;wQ /wP;bG1 bP-;wB2 wQ\;bA1 -bP;wB1 wP;bG2 /bA1;wB2 /wQ;bA1 bG1\;wB2 -wQ;bA2 bG1/;wA1 wQ\;bA1 /wQ;wS1 wA1/;bA1 wA1-;wA1 \bP;bA2 bA1/;wA1 bA2/;bA3 /bA1;wG1 -wB2;bB1 bA3\;wA1 /bA3;bA4 bA2-;wA1 /wQ;bG3 bA4/;wG2 \wG1;bG1 wB1/;wA1 /wB2;bB2 /bB1;wB1 wS1;bG1 /wQ;wB2 bG1;bS1 bB2-;wS2 -wG2;bS2 \bG3;wS3 /wA1;bB2 bS1;wG3 -wS2;bB2 /bB1",
+    r"wS1;bP wS1\;wG1 -wS1;bB1 bP-;wQ -wG1;bQ /bP;wG2 \wS1;bB2 bQ\;wQ -wG2;bB1 bP;wP wG2/;bB2 /bQ;wB1 /wG1;bB2 -bQ;wA1 -wP;bG1 bQ-;wA1 -wB1;bA1 bG1-;wS2 \wP;bA1 /bQ;wA2 wP-;bA1 /wA2;wG3 \wQ;bG2 bG1/;wS3 \wA2;bA1 -wA1;wS1 wA2-;bA1 /bG1;wA1 /wG3;bA2 bG2/;wB2 wS1-;bA1 bG1-;wA3 /wA2;bA1 -wA1;wA4 wB2/;bA2 -wB1;wA3 \wA4;bA2 \bB1;wA3 -bA1;bA2 bG1\;wA3 /wS1;bA1 \wG3;wA4 /bQ;bA1 \wS3;wA4 -wA1;bS1 /bG1",
+];
+
+/// Games with all expansions and 20 tiles in play. Between them, every insect has a tile that
+/// can move.
+pub const EXPANSION_GAMES: [&str; 3] = [
+    r"wA1;bS1 wA1-;wL /wA1;bS2 bS1/;wA2 -wL;bL bS1\;wQ \wA1;bQ /bL;wA3 wQ/;bB1 bL/;wP -wA3;bB2 /bQ;wA3 /wL;bP bS2/;wA3 -bS2;bA1 bB1-;wA3 bB2-;bM \bP;wA2 /bA1;bG1 -bB2;wA3 wQ/;bA2 bP/;wB1 /wQ;bA2 wA2\;wP -wQ;bA1 /bB2;wG1 \wA3;bA3 bA2-",
+    r"wS1;bA1 wS1/;wS2 wS1\;bL bA1/;wL -wS1;bA2 bL/;wQ /wS2;bQ \bA2;wB1 /wS1;bG1 bL-;wB2 /wQ;bA3 /bG1;wL -wB1;bA3 bA2/;wM wB2-;bA3 /wB1;wP /wB2;bG1 \bQ;wG1 -wP;bG1 bL-;wG2 wM\;bA3 bQ/;wG3 -wL;bB1 /bQ;wA1 /wG3;bP bA3/",
+    r"wL;bG1 \wL;wG1 wL-;bM \bG1;wP /wL;bG2 bM/;wQ wG1/;bQ bG2-;wA1 /wG1;bS1 \bG2;wA2 wG1-;bS2 -bS1;wB1 /wP;bP bQ/;wA2 \bS1;bL /bQ;wA2 -wP;bB1 bQ-;wA2 wA1-;bA1 bS1/;wB2 -wP;bA1 wA2-;wB1 /wA1;bG3 bB1/;wS1 wB1\",
+];
+
+/// The positions after the `games` written in notation, played with `rules`.
+pub fn positions(rules: &RuleSet, games: &[&str]) -> Vec<GameState> {
+    games
+        .iter()
+        .map(|moves| {
+            let mut game_state = GameState::with_rules(rules.clone());
+            play_moves(&mut game_state, moves).unwrap();
+            game_state
+        })
+        .collect()
+}
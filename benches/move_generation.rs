@@ -0,0 +1,71 @@
+//! How long it takes to find the moves of single tiles and of whole positions.
+
+mod common;
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use hive_bevy::data::enums::InsectType;
+use hive_bevy::rule_set::RuleSet;
+use hive_bevy::rules;
+
+/// Every insect, which needs all expansions.
+const INSECTS: [InsectType; 8] = [
+    InsectType::Queen,
+    InsectType::Beetle,
+    InsectType::Grasshopper,
+    InsectType::Spider,
+    InsectType::Ant,
+    InsectType::Pillbug,
+    InsectType::Mosquito,
+    InsectType::Ladybug,
+];
+
+fn all_expansions() -> RuleSet {
+    RuleSet::from_game_type("Base+MLP").unwrap()
+}
+
+fn insects(c: &mut Criterion) {
+    let mut group = c.benchmark_group("insects");
+    for insect in INSECTS {
+        //a tile of the insect that can move in a game with most pieces in play
+        let (board, position) = common::positions(&all_expansions(), &common::EXPANSION_GAMES)
+            .into_iter()
+            .find_map(|game_state| {
+                let board = game_state.board();
+                let position = board.positions().copied().find(|position| {
+                    board.get_top(position).unwrap().insect == insect
+                        && !rules::get_moves_for_tile(board, *position).is_empty()
+                })?;
+                Some((board.clone(), position))
+            })
+            .unwrap();
+
+        group.bench_function(format!("{insect:?}"), |b| {
+            b.iter(|| rules::get_moves_for_tile(black_box(&board), position))
+        });
+    }
+    group.finish();
+}
+
+fn legal_moves(c: &mut Criterion) {
+    let mut group = c.benchmark_group("legal_moves");
+    for (name, games) in [("midgame", common::MIDGAMES), ("endgame", common::ENDGAMES)] {
+        let positions = common::positions(&RuleSet::default(), &games);
+
+        //fresh copies, because a game state keeps the pinned tiles it found
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || positions.clone(),
+                |positions| {
+                    for game_state in &positions {
+                        black_box(game_state.legal_moves());
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, insects, legal_moves);
+criterion_main!(benches);
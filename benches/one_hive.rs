@@ -0,0 +1,42 @@
+//! Compares finding every pinned tile with one search over the board with checking each tile on
+//! its own, which is what move generation did before. Both start from scratch for every board.
+
+mod common;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use hive_bevy::game_state::Board;
+use hive_bevy::rule_set::RuleSet;
+use hive_bevy::rules;
+
+fn pinned_per_tile(board: &Board) -> usize {
+    board
+        .positions()
+        .filter(|position| {
+            board.height(position) == 1
+                && !rules::check_moving_piece_allowed(&board.get_without(position))
+        })
+        .count()
+}
+
+fn one_hive(c: &mut Criterion) {
+    let mut group = c.benchmark_group("one_hive");
+    for (name, games) in [("midgame", common::MIDGAMES), ("endgame", common::ENDGAMES)] {
+        let game_state = &common::positions(&RuleSet::default(), &games[..1])[0];
+        let board = game_state.board();
+        assert_eq!(
+            pinned_per_tile(board),
+            rules::get_pinned_positions(board).len()
+        );
+
+        group.bench_function(format!("per_tile/{name}"), |b| {
+            b.iter(|| pinned_per_tile(black_box(board)))
+        });
+        group.bench_function(format!("articulation_points/{name}"), |b| {
+            b.iter(|| rules::get_pinned_positions(black_box(board)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, one_hive);
+criterion_main!(benches);
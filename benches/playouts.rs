@@ -0,0 +1,37 @@
+//! How many random games can be played per second, which is what limits the MCTS bot.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use hive_bevy::bot::Bot;
+use hive_bevy::game_state::GameState;
+use hive_bevy::rule_set::RuleSet;
+
+/// Random games don't always end by themselves.
+const MOVE_LIMIT: u32 = 100;
+
+fn playouts(c: &mut Criterion) {
+    let rules = RuleSet {
+        move_limit: Some(MOVE_LIMIT),
+        ..RuleSet::default()
+    };
+
+    let mut group = c.benchmark_group("playouts");
+    group.throughput(Throughput::Elements(1));
+    group.sample_size(20);
+
+    let mut seed = 0;
+    group.bench_function("random_game", |b| {
+        b.iter(|| {
+            seed += 1;
+            let bot = Bot::Random { seed };
+            let mut game_state = GameState::with_rules(rules.clone());
+            while game_state.result().is_none() {
+                game_state.apply(bot.best_move(&game_state));
+            }
+            game_state
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, playouts);
+criterion_main!(benches);
@@ -0,0 +1,193 @@
+//! A computer opponent searching the legal moves of a [`GameState`] with alpha-beta pruning.
+
+use crate::data::enums::GameResult;
+use crate::evaluation::{evaluate, EvaluationWeights};
+use crate::game_state::{GameState, Move};
+use bevy::utils::{Duration, Instant};
+
+const WIN_SCORE: i32 = 1_000_000;
+
+/// How far the search looks ahead. The search deepens one ply at a time and stops at
+/// `depth` or when `time_budget` runs out, whichever comes first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchSettings {
+    pub depth: u32,
+    pub time_budget: Duration,
+    pub weights: EvaluationWeights,
+}
+
+impl Default for SearchSettings {
+    fn default() -> Self {
+        SearchSettings {
+            depth: 3,
+            time_budget: Duration::from_secs(2),
+            weights: EvaluationWeights::default(),
+        }
+    }
+}
+
+/// Returns the best move for the current player of `game_state`, or [`Move::Pass`] if there
+/// is nothing else to play. The first ply is always searched completely, so a move is
+/// returned even if the time budget is exceeded.
+pub fn best_move(game_state: &GameState, settings: &SearchSettings) -> Move {
+    //a budget too large for an instant means there is no deadline at all
+    let deadline = Instant::now().checked_add(settings.time_budget);
+    let mut game_state = game_state.clone();
+
+    let mut moves = game_state.legal_moves();
+    if moves.is_empty() {
+        return Move::Pass;
+    }
+
+    for depth in 1..=settings.depth.max(1) {
+        let mut best = None;
+        let mut alpha = -WIN_SCORE - 1;
+
+        for mv in &moves {
+            game_state.apply(*mv);
+            let score = negamax(
+                &mut game_state,
+                depth - 1,
+                -WIN_SCORE - 1,
+                -alpha,
+                1,
+                deadline.filter(|_| depth > 1),
+                &settings.weights,
+            );
+            game_state.undo();
+
+            let Some(score) = score else {
+                break;
+            };
+            if best.is_none() || -score > alpha {
+                alpha = -score;
+                best = Some(*mv);
+            }
+        }
+
+        let Some(best) = best else {
+            break;
+        };
+        //searching the best move of the previous depth first gives the most cutoffs
+        let index = moves.iter().position(|mv| *mv == best).unwrap();
+        moves[..=index].rotate_right(1);
+
+        let out_of_time = deadline.is_some_and(|deadline| Instant::now() > deadline);
+        if alpha.abs() > WIN_SCORE / 2 || out_of_time {
+            break;
+        }
+    }
+
+    moves[0]
+}
+
+/// The score of `game_state` for its current player, or `None` if the deadline passed.
+fn negamax(
+    game_state: &mut GameState,
+    depth: u32,
+    mut alpha: i32,
+    beta: i32,
+    ply: i32,
+    deadline: Option<Instant>,
+    weights: &EvaluationWeights,
+) -> Option<i32> {
+    if deadline.is_some_and(|deadline| Instant::now() > deadline) {
+        return None;
+    }
+
+    let current_player = game_state.current_player();
+    match game_state.result() {
+        Some(GameResult::Draw(_)) => return Some(0),
+        //winning sooner is better than winning later
+        Some(GameResult::PlayerWon(player, _)) if player == current_player => {
+            return Some(WIN_SCORE - ply)
+        }
+        Some(GameResult::PlayerWon(..)) => return Some(-WIN_SCORE + ply),
+        None => {}
+    }
+
+    if depth == 0 {
+        return Some(evaluate(game_state, current_player, weights));
+    }
+
+    let mut moves = game_state.legal_moves();
+    if moves.is_empty() {
+        moves.push(Move::Pass);
+    }
+
+    let mut best = -WIN_SCORE - 1;
+    for mv in moves {
+        game_state.apply(mv);
+        let score = negamax(
+            game_state,
+            depth - 1,
+            -beta,
+            -alpha,
+            ply + 1,
+            deadline,
+            weights,
+        );
+        game_state.undo();
+
+        let score = -score?;
+        best = best.max(score);
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    Some(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notation::{format_move, play, WINNING_POSITION};
+    use crate::rule_set::RuleSet;
+
+    #[test]
+    fn finds_the_winning_move() {
+        let game_state = play(WINNING_POSITION);
+        let settings = SearchSettings {
+            depth: 2,
+            time_budget: Duration::from_secs(60),
+            ..Default::default()
+        };
+
+        let mv = best_move(&game_state, &settings);
+        let mut after = game_state.clone();
+        after.try_apply(mv).unwrap();
+        assert_eq!(
+            after.result().and_then(|result| result.winner()),
+            Some(game_state.current_player()),
+            "{}",
+            format_move(&game_state, &mv)
+        );
+    }
+
+    #[test]
+    fn returns_a_legal_move_without_time() {
+        let game_state = play("wS1;bS1 -wS1;wQ wS1-;bQ -bS1");
+        let settings = SearchSettings {
+            depth: 10,
+            time_budget: Duration::ZERO,
+            ..Default::default()
+        };
+
+        let mv = best_move(&game_state, &settings);
+        assert!(game_state.is_legal(&mv));
+    }
+
+    #[test]
+    fn passes_without_legal_moves() {
+        let game_state = GameState::with_rules(RuleSet {
+            pieces: vec![],
+            ..RuleSet::default()
+        });
+        assert_eq!(
+            best_move(&game_state, &SearchSettings::default()),
+            Move::Pass
+        );
+    }
+}
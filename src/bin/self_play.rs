@@ -0,0 +1,105 @@
+//! Plays the computer opponents against each other without opening a window and prints how
+//! they did, e.g. `cargo run --release --bin self_play -- 10 42` for 10 games per pairing
+//! starting with the seed 42.
+
+use hive_bevy::ai::SearchSettings;
+use hive_bevy::bot::Bot;
+use hive_bevy::data::enums::{GameResult, Player};
+use hive_bevy::game_state::GameState;
+use hive_bevy::mcts::MctsSettings;
+use hive_bevy::notation::format_game;
+use std::time::Duration;
+
+/// Games that take longer than this are counted as a draw.
+const MAX_MOVES: usize = 200;
+
+#[derive(Default)]
+struct Score {
+    wins: u32,
+    draws: u32,
+    losses: u32,
+}
+
+/// Plays a game and returns its result, or `None` if it hit [`MAX_MOVES`].
+fn play_game(player1: &Bot, player2: &Bot) -> (Option<GameResult>, GameState) {
+    let mut game_state = GameState::new();
+
+    while game_state.history().len() < MAX_MOVES {
+        if let Some(result) = game_state.result() {
+            return (Some(result), game_state);
+        }
+
+        let bot = match game_state.current_player() {
+            Player::Player1 => player1,
+            Player::Player2 => player2,
+        };
+        let mv = bot.best_move(&game_state);
+        game_state.apply(mv);
+    }
+
+    (game_state.result(), game_state)
+}
+
+fn main() {
+    let mut arguments = std::env::args().skip(1);
+    let games: u32 = arguments
+        .next()
+        .map(|games| {
+            games
+                .parse()
+                .expect("The number of games needs to be a number")
+        })
+        .unwrap_or(4);
+    let seed: u64 = arguments
+        .next()
+        .map(|seed| seed.parse().expect("The seed needs to be a number"))
+        .unwrap_or(0);
+
+    let alpha_beta = Bot::AlphaBeta(SearchSettings {
+        depth: 2,
+        time_budget: Duration::from_secs(1),
+        ..Default::default()
+    });
+    let mcts = Bot::Mcts(MctsSettings {
+        iterations: 500,
+        time_budget: Duration::from_secs(1),
+        ..Default::default()
+    });
+    let random = Bot::Random { seed: 0 };
+
+    for (bot, opponent) in [(mcts, alpha_beta), (mcts, random), (alpha_beta, random)] {
+        let mut score = Score::default();
+
+        for game in 0..games {
+            //both bots get to play white equally often
+            let bot_player = match game % 2 {
+                0 => Player::Player1,
+                _ => Player::Player2,
+            };
+            //every game gets different seeds so they don't all play out the same
+            let game_seed = seed.wrapping_add(u64::from(game) * 2);
+            let bot = bot.with_seed(game_seed);
+            let opponent = opponent.with_seed(game_seed.wrapping_add(1));
+            let (result, game_state) = match bot_player {
+                Player::Player1 => play_game(&bot, &opponent),
+                Player::Player2 => play_game(&opponent, &bot),
+            };
+
+            match result {
+                Some(GameResult::PlayerWon(player, _)) if player == bot_player => score.wins += 1,
+                Some(GameResult::PlayerWon(..)) => score.losses += 1,
+                Some(GameResult::Draw(_)) | None => score.draws += 1,
+            }
+            println!(
+                "{bot} vs {opponent}, game {}: {}",
+                game + 1,
+                format_game(&game_state)
+            );
+        }
+
+        println!(
+            "{bot} vs {opponent}: {} wins, {} draws, {} losses\n",
+            score.wins, score.draws, score.losses
+        );
+    }
+}
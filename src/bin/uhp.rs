@@ -0,0 +1,322 @@
+//! An engine speaking the Universal Hive Protocol on stdin/stdout, so the rules of this
+//! project can be used by Hive GUIs and tournament tools without opening a window.
+//! See <https://github.com/jonthysell/Mzinga/wiki/UniversalHiveProtocol>.
+
+use hive_bevy::ai::{best_move, SearchSettings};
+use hive_bevy::data::enums::{GameResult, Player};
+use hive_bevy::game_state::{GameState, Move};
+use hive_bevy::notation::{format_game, format_move, parse_move, play_moves};
+use hive_bevy::rule_set::RuleSet;
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+const BASE_GAME: &str = "Base";
+/// The expansion pieces [`RuleSet::from_game_type`] understands.
+const CAPABILITIES: &str = "Mosquito;Ladybug;Pillbug";
+
+#[derive(Default, Clone)]
+struct Engine {
+    game_state: Option<GameState>,
+    game_type: String,
+}
+
+impl Engine {
+    fn handle(&mut self, line: &str) -> Result<String, String> {
+        let (command, arguments) = match line.split_once(' ') {
+            Some((command, arguments)) => (command, arguments.trim()),
+            None => (line, ""),
+        };
+
+        match command {
+            "info" => Ok(format!(
+                "id hive-bevy v{}\n{CAPABILITIES}",
+                env!("CARGO_PKG_VERSION")
+            )),
+            "newgame" => self.new_game(arguments),
+            "play" => self.play(arguments),
+            "pass" => self.play("pass"),
+            "validmoves" => self.valid_moves(),
+            "bestmove" => self.best_move(arguments),
+            "undo" => self.undo(arguments),
+            "options" => Ok(String::new()),
+            _ => Err(format!("Invalid command '{command}'")),
+        }
+    }
+
+    fn game_state(&self) -> Result<&GameState, String> {
+        self.game_state
+            .as_ref()
+            .ok_or_else(|| "No game in progress".to_string())
+    }
+
+    fn new_game(&mut self, arguments: &str) -> Result<String, String> {
+        let mut parts = arguments.split(';');
+        let game_type = match parts.next() {
+            None | Some("") => BASE_GAME,
+            Some(game_type) => game_type,
+        };
+
+        // the game state and turn strings can be derived from the moves
+        let moves = parts.skip(2).collect::<Vec<_>>().join(";");
+        let rule_set = RuleSet::from_game_type(game_type)
+            .ok_or_else(|| format!("Unsupported game type '{game_type}'"))?;
+        let mut game_state = GameState::with_rules(rule_set);
+        play_moves(&mut game_state, &moves).map_err(|e| e.to_string())?;
+
+        self.game_state = Some(game_state);
+        self.game_type = game_type.to_string();
+        self.game_string()
+    }
+
+    fn play(&mut self, move_string: &str) -> Result<String, String> {
+        let game_state = self.game_state()?;
+        if game_state.result().is_some() {
+            return Ok("invalidmove The game is over".to_string());
+        }
+
+        let mv = match parse_move(game_state, move_string) {
+            Ok(mv) => mv,
+            Err(error) => return Ok(format!("invalidmove {error}")),
+        };
+
+        let game_state = self.game_state.as_mut().unwrap();
+        if game_state.try_apply(mv).is_err() {
+            return Ok(format!("invalidmove {move_string} is not a legal move"));
+        }
+
+        self.game_string()
+    }
+
+    fn valid_moves(&self) -> Result<String, String> {
+        let game_state = self.game_state()?;
+        if game_state.result().is_some() {
+            return Ok(String::new());
+        }
+
+        let moves = game_state.legal_moves();
+        if moves.is_empty() {
+            return Ok(format_move(game_state, &Move::Pass));
+        }
+
+        Ok(moves
+            .iter()
+            .map(|mv| format_move(game_state, mv))
+            .collect::<Vec<_>>()
+            .join(";"))
+    }
+
+    /// Searches with the default settings, or until `depth N` or `time hh:mm:ss` if given.
+    fn best_move(&self, arguments: &str) -> Result<String, String> {
+        let game_state = self.game_state()?;
+        if game_state.result().is_some() {
+            return Err("The game is over".to_string());
+        }
+
+        let mut settings = SearchSettings::default();
+        match arguments.split_once(' ') {
+            None if arguments.is_empty() => {}
+            Some(("depth", depth)) => {
+                settings.depth = depth
+                    .parse()
+                    .map_err(|_| format!("Invalid depth '{depth}'"))?;
+                settings.time_budget = Duration::MAX;
+            }
+            Some(("time", time)) => {
+                settings.depth = u32::MAX;
+                settings.time_budget = parse_time(time)?;
+            }
+            _ => return Err(format!("Invalid arguments '{arguments}'")),
+        }
+
+        let mv = best_move(game_state, &settings);
+        Ok(format_move(game_state, &mv))
+    }
+
+    fn undo(&mut self, arguments: &str) -> Result<String, String> {
+        let count = match arguments {
+            "" => 1,
+            count => count
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid number of moves '{count}'"))?,
+        };
+
+        let game_state = self
+            .game_state
+            .as_mut()
+            .ok_or_else(|| "No game in progress".to_string())?;
+        if count > game_state.history().len() {
+            return Err(format!("Unable to undo {count} moves"));
+        }
+        for _ in 0..count {
+            game_state.undo();
+        }
+
+        self.game_string()
+    }
+
+    fn game_string(&self) -> Result<String, String> {
+        let game_state = self.game_state()?;
+
+        let state = match game_state.result() {
+            _ if game_state.history().len() == 0 => "NotStarted",
+            None => "InProgress",
+            Some(GameResult::Draw(_)) => "Draw",
+            Some(GameResult::PlayerWon(Player::Player1, _)) => "WhiteWins",
+            Some(GameResult::PlayerWon(Player::Player2, _)) => "BlackWins",
+        };
+        let color = match game_state.current_player() {
+            Player::Player1 => "White",
+            Player::Player2 => "Black",
+        };
+        let turn = game_state.history().len() / 2 + 1;
+
+        let mut game_string = format!("{};{state};{color}[{turn}]", self.game_type);
+        if game_state.history().len() > 0 {
+            game_string = format!("{game_string};{}", format_game(game_state));
+        }
+
+        Ok(game_string)
+    }
+}
+
+fn parse_time(time: &str) -> Result<Duration, String> {
+    let error = || format!("Invalid time '{time}'");
+
+    let mut seconds = 0;
+    for part in time.split(':') {
+        seconds = seconds * 60 + part.parse::<u64>().map_err(|_| error())?;
+    }
+    if time.split(':').count() != 3 {
+        return Err(error());
+    }
+
+    Ok(Duration::from_secs(seconds))
+}
+
+fn main() {
+    let mut engine = Engine::default();
+    let mut stdout = std::io::stdout().lock();
+
+    let info = engine.handle("info").unwrap();
+    writeln!(stdout, "{info}\nok").unwrap();
+    stdout.flush().unwrap();
+
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line == "exit" {
+            break;
+        }
+
+        match engine.handle(line) {
+            Ok(output) if output.is_empty() => writeln!(stdout, "ok"),
+            Ok(output) => writeln!(stdout, "{output}\nok"),
+            Err(error) => writeln!(stdout, "err {error}\nok"),
+        }
+        .unwrap();
+        stdout.flush().unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine(commands: &[&str]) -> Engine {
+        let mut engine = Engine::default();
+        for command in commands {
+            engine.handle(command).unwrap();
+        }
+        engine
+    }
+
+    #[test]
+    fn info_lists_the_expansions() {
+        let info = Engine::default().handle("info").unwrap();
+        assert!(info.starts_with("id hive-bevy v"));
+        assert_eq!(info.lines().nth(1), Some(CAPABILITIES));
+    }
+
+    #[test]
+    fn newgame_starts_or_replays_a_game() {
+        let mut engine = Engine::default();
+        assert_eq!(
+            engine.handle("newgame").unwrap(),
+            "Base;NotStarted;White[1]"
+        );
+        assert_eq!(
+            engine
+                .handle("newgame Base+M;InProgress;White[2];wS1;bM wS1-")
+                .unwrap(),
+            "Base+M;InProgress;White[2];wS1;bM wS1-"
+        );
+        assert!(engine.handle("newgame Base+X").is_err());
+        assert!(engine
+            .handle("newgame Base;InProgress;White[2];wS1;wS2 wS1-")
+            .is_err());
+    }
+
+    #[test]
+    fn play_answers_with_the_game_string() {
+        let mut engine = engine(&["newgame Base"]);
+        assert_eq!(
+            engine.handle("play wS1").unwrap(),
+            "Base;InProgress;Black[1];wS1"
+        );
+        assert_eq!(
+            engine.handle("play bG1 -wS1").unwrap(),
+            "Base;InProgress;White[2];wS1;bG1 -wS1"
+        );
+        assert_eq!(
+            engine.handle("play wA1 -bG1").unwrap(),
+            "invalidmove wA1 -bG1 is not a legal move"
+        );
+        assert!(engine
+            .handle("play xyz")
+            .unwrap()
+            .starts_with("invalidmove"));
+        assert!(Engine::default().handle("play wS1").is_err());
+    }
+
+    #[test]
+    fn validmoves_can_all_be_played() {
+        //the queen can't be the first piece in the base game
+        let mut engine = engine(&["newgame Base"]);
+        let mut first_moves: Vec<_> = engine
+            .handle("validmoves")
+            .unwrap()
+            .split(';')
+            .map(String::from)
+            .collect();
+        first_moves.sort();
+        assert_eq!(first_moves, ["wA1", "wB1", "wG1", "wS1"]);
+
+        engine.handle("play wS1").unwrap();
+        let valid_moves = engine.handle("validmoves").unwrap();
+        let valid_moves: Vec<_> = valid_moves.split(';').collect();
+        assert!(valid_moves.contains(&"bA1 -wS1"), "{valid_moves:?}");
+
+        for mv in valid_moves {
+            let mut engine = engine.clone();
+            let game_string = engine.handle(&format!("play {mv}")).unwrap();
+            assert!(game_string.ends_with(mv), "{mv}: {game_string}");
+        }
+    }
+
+    #[test]
+    fn undo_takes_back_moves() {
+        let mut engine = engine(&["newgame Base", "play wS1", "play bS1 wS1-", "play wQ -wS1"]);
+        assert_eq!(
+            engine.handle("undo").unwrap(),
+            "Base;InProgress;White[2];wS1;bS1 wS1-"
+        );
+        assert_eq!(engine.handle("undo 2").unwrap(), "Base;NotStarted;White[1]");
+        assert!(engine.handle("undo").is_err());
+        assert!(engine.handle("undo x").is_err());
+    }
+}
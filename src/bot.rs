@@ -0,0 +1,75 @@
+//! The computer opponents, so that the game and the self-play harness can use them alike.
+
+use crate::ai::SearchSettings;
+use crate::game_state::{GameState, Move};
+use crate::mcts::MctsSettings;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bot {
+    /// Plays a random legal move, mostly useful as a baseline.
+    Random {
+        seed: u64,
+    },
+    AlphaBeta(SearchSettings),
+    Mcts(MctsSettings),
+}
+
+impl Bot {
+    /// The same bot with a different seed, if it uses random numbers at all.
+    pub fn with_seed(self, seed: u64) -> Bot {
+        match self {
+            Bot::Random { .. } => Bot::Random { seed },
+            Bot::AlphaBeta(settings) => Bot::AlphaBeta(settings),
+            Bot::Mcts(settings) => Bot::Mcts(MctsSettings { seed, ..settings }),
+        }
+    }
+
+    pub fn best_move(&self, game_state: &GameState) -> Move {
+        match self {
+            Bot::Random { seed } => {
+                let moves = game_state.legal_moves();
+                if moves.is_empty() {
+                    return Move::Pass;
+                }
+
+                let mut rng = SmallRng::seed_from_u64(seed ^ game_state.history().len() as u64);
+                moves[rng.gen_range(0..moves.len())]
+            }
+            Bot::AlphaBeta(settings) => crate::ai::best_move(game_state, settings),
+            Bot::Mcts(settings) => crate::mcts::best_move(game_state, settings),
+        }
+    }
+}
+
+impl fmt::Display for Bot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bot::Random { .. } => write!(f, "Random"),
+            Bot::AlphaBeta(settings) => write!(f, "AlphaBeta(depth {})", settings.depth),
+            Bot::Mcts(settings) => write!(f, "MCTS({} iterations)", settings.iterations),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notation::format_game;
+
+    #[test]
+    fn random_bot_plays_the_same_game_in_every_run() {
+        let bot = Bot::Random { seed: 3 };
+        let mut game_state = GameState::new();
+        for _ in 0..12 {
+            game_state.apply(bot.best_move(&game_state));
+        }
+
+        assert_eq!(
+            format_game(&game_state),
+            "wS1;bG1 /wS1;wQ wS1/;bS1 -bG1;wQ \\wS1;bQ bG1\\;wG1 wS1-;bQ /bG1;wA1 \\wQ;bP /bS1;wA2 wA1/;bB1 \\bS1"
+        );
+    }
+}
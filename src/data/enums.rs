@@ -1,4 +1,5 @@
 use bevy::prelude::{Component, States};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
 pub enum AppState {
@@ -8,15 +9,29 @@ pub enum AppState {
     MovingTile,
     MoveFinished,
     PlayerWon,
+    /// Respawns all tiles after the [`GameState`](crate::game_state::GameState) was changed
+    /// outside of a regular move, e.g. by undo or redo.
+    Rebuilding,
+    /// Steps through the moves of a recorded game without allowing any input on the board.
+    Replay,
 }
 
-#[derive(Component, Clone, Copy, PartialEq)]
+#[derive(Component, Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum Player {
     Player1,
     Player2,
 }
 
-#[derive(Component, Default, Copy, Clone, PartialEq)]
+impl Player {
+    pub fn other(&self) -> Player {
+        match self {
+            Player::Player1 => Player::Player2,
+            Player::Player2 => Player::Player1,
+        }
+    }
+}
+
+#[derive(Component, Default, Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum InsectType {
     #[default]
     Ant,
@@ -24,9 +39,41 @@ pub enum InsectType {
     Spider,
     Grasshopper,
     Beetle,
+    Pillbug,
+    Mosquito,
+    Ladybug,
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum GameResult {
-    Draw,
-    PlayerWon(Player),
+    Draw(DrawReason),
+    PlayerWon(Player, WinReason),
+}
+
+impl GameResult {
+    pub fn winner(&self) -> Option<Player> {
+        match self {
+            GameResult::Draw(_) => None,
+            GameResult::PlayerWon(player, _) => Some(*player),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum WinReason {
+    QueenSurrounded,
+    Resignation,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DrawReason {
+    /// Both queens were surrounded by the same move.
+    QueensSurrounded,
+    /// Neither player has a legal move left.
+    NoLegalMoves,
+    Agreement,
+    /// The same position came up for the third time.
+    Repetition,
+    /// The move limit of the [`RuleSet`](crate::rule_set::RuleSet) was reached.
+    MoveLimit,
 }
@@ -0,0 +1,210 @@
+//! A static rating of positions, used by the computer opponents.
+
+use crate::data::enums::{InsectType, Player};
+use crate::game_state::GameState;
+use crate::hex_coordinate::ALL_DIRECTIONS;
+use crate::rules;
+use serde::{Deserialize, Serialize};
+
+/// How much each feature of a position is worth. Every feature is counted for both players,
+/// so a weight adds to the score for `player` and subtracts for their opponent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvaluationWeights {
+    /// Per empty space around the own queen.
+    pub queen_free_neighbour: i32,
+    /// Per piece on the ground that can't move without splitting the hive.
+    pub pinned_piece: i32,
+    /// Per possible move of a piece, depending on its insect.
+    pub ant_mobility: i32,
+    pub queen_mobility: i32,
+    pub spider_mobility: i32,
+    pub grasshopper_mobility: i32,
+    pub beetle_mobility: i32,
+    pub pillbug_mobility: i32,
+    pub mosquito_mobility: i32,
+    pub ladybug_mobility: i32,
+    /// Per piece that has not been placed yet.
+    pub piece_in_hand: i32,
+    /// Per own beetle on top of the queen of the opponent.
+    pub beetle_on_queen: i32,
+}
+
+impl Default for EvaluationWeights {
+    fn default() -> Self {
+        EvaluationWeights {
+            queen_free_neighbour: 20,
+            pinned_piece: -5,
+            ant_mobility: 2,
+            queen_mobility: 3,
+            spider_mobility: 1,
+            grasshopper_mobility: 1,
+            beetle_mobility: 2,
+            pillbug_mobility: 2,
+            mosquito_mobility: 2,
+            ladybug_mobility: 2,
+            piece_in_hand: 1,
+            beetle_on_queen: 30,
+        }
+    }
+}
+
+impl EvaluationWeights {
+    pub fn mobility(&self, insect: InsectType) -> i32 {
+        match insect {
+            InsectType::Ant => self.ant_mobility,
+            InsectType::Queen => self.queen_mobility,
+            InsectType::Spider => self.spider_mobility,
+            InsectType::Grasshopper => self.grasshopper_mobility,
+            InsectType::Beetle => self.beetle_mobility,
+            InsectType::Pillbug => self.pillbug_mobility,
+            InsectType::Mosquito => self.mosquito_mobility,
+            InsectType::Ladybug => self.ladybug_mobility,
+        }
+    }
+}
+
+/// Rates the position for `player`, higher is better. The score for the opponent is always
+/// the negated score, and the result of a finished game is not taken into account.
+pub fn evaluate(game_state: &GameState, player: Player, weights: &EvaluationWeights) -> i32 {
+    let board = game_state.board();
+    let sign = |owner: Player| if owner == player { 1 } else { -1 };
+    let pinned = game_state.pinned_positions();
+    let mut score = 0;
+
+    for (position, stack) in board.stacks() {
+        let top = board.get_top(position).unwrap();
+
+        for (level, tile) in stack.enumerate() {
+            if tile.insect != InsectType::Queen {
+                continue;
+            }
+
+            let free_neighbours = ALL_DIRECTIONS
+                .iter()
+                .filter(|direction| !board.contains(&position.get_relative(direction)))
+                .count() as i32;
+            score += sign(tile.player) * weights.queen_free_neighbour * free_neighbours;
+
+            for beetle in board.get_stack(position).skip(level + 1) {
+                if beetle.insect == InsectType::Beetle && beetle.player != tile.player {
+                    score += sign(beetle.player) * weights.beetle_on_queen;
+                }
+            }
+        }
+
+        let moves = rules::get_moves_for_tile_with_pinned(board, *position, pinned).len() as i32;
+        score += sign(top.player) * weights.mobility(top.insect) * moves;
+
+        if pinned.contains(position) {
+            score += sign(top.player) * weights.pinned_piece;
+        }
+    }
+
+    for owner in [Player::Player1, Player::Player2] {
+        let in_hand = game_state.inventory(owner).pieces.len() as i32;
+        score += sign(owner) * weights.piece_in_hand * in_hand;
+    }
+
+    score
+}
+
+/// How many more neighbours the queen of the opponent of `player` has than their own.
+pub fn queen_pressure(game_state: &GameState, player: Player) -> i32 {
+    let mut pressure = 0;
+
+    for (position, stack) in game_state.board().stacks() {
+        for tile in stack.filter(|tile| tile.insect == InsectType::Queen) {
+            let neighbours = ALL_DIRECTIONS
+                .iter()
+                .filter(|direction| {
+                    game_state
+                        .board()
+                        .contains(&position.get_relative(direction))
+                })
+                .count() as i32;
+
+            match tile.player == player {
+                true => pressure -= neighbours,
+                false => pressure += neighbours,
+            }
+        }
+    }
+
+    pressure
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notation::play;
+
+    const NONE: EvaluationWeights = EvaluationWeights {
+        queen_free_neighbour: 0,
+        pinned_piece: 0,
+        ant_mobility: 0,
+        queen_mobility: 0,
+        spider_mobility: 0,
+        grasshopper_mobility: 0,
+        beetle_mobility: 0,
+        pillbug_mobility: 0,
+        mosquito_mobility: 0,
+        ladybug_mobility: 0,
+        piece_in_hand: 0,
+        beetle_on_queen: 0,
+    };
+
+    #[test]
+    fn scores_are_symmetric() {
+        let weights = EvaluationWeights::default();
+        for moves in ["", "wQ", "wS1;bS1 -wS1;wQ wS1-;bQ -bS1;wA1 wQ\\"] {
+            let game_state = play(moves);
+            assert_eq!(
+                evaluate(&game_state, Player::Player1, &weights),
+                -evaluate(&game_state, Player::Player2, &weights),
+                "{moves}"
+            );
+        }
+    }
+
+    #[test]
+    fn counts_queens_beetles_and_pieces_in_hand() {
+        //wB1 climbed onto bQ, which leaves three free spaces around bQ and five around wQ
+        let game_state = play("wQ;bQ wQ-;wB1 \\wQ;bB1 bQ/;wB1 wQ/;bA1 bQ\\;wB1 bQ");
+
+        let queens = EvaluationWeights {
+            queen_free_neighbour: 1,
+            ..NONE
+        };
+        assert_eq!(evaluate(&game_state, Player::Player1, &queens), 2);
+
+        let beetles = EvaluationWeights {
+            beetle_on_queen: 1,
+            ..NONE
+        };
+        assert_eq!(evaluate(&game_state, Player::Player1, &beetles), 1);
+
+        let in_hand = EvaluationWeights {
+            piece_in_hand: 1,
+            ..NONE
+        };
+        assert_eq!(evaluate(&game_state, Player::Player1, &in_hand), 1);
+    }
+
+    #[test]
+    fn counts_pinned_pieces_and_mobility() {
+        //wG1 is pinned between wA1 and bG1, while bG1 can still jump over both
+        let game_state = play("wG1;bG1 wG1-;wA1 -wG1");
+
+        let pinned = EvaluationWeights {
+            pinned_piece: -1,
+            ..NONE
+        };
+        assert_eq!(evaluate(&game_state, Player::Player1, &pinned), -1);
+
+        let grasshoppers = EvaluationWeights {
+            grasshopper_mobility: 1,
+            ..NONE
+        };
+        assert_eq!(evaluate(&game_state, Player::Player1, &grasshoppers), -1);
+    }
+}
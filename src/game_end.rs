@@ -0,0 +1,82 @@
+use crate::opponent::PlayerControllers;
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::{
+    in_state, on_event, Event, EventReader, IntoSystemConfigs, NextState, Res, ResMut, Resource,
+};
+use hive_bevy::data::components::CurrentPlayer;
+use hive_bevy::data::enums::{AppState, Player};
+use hive_bevy::game_state::GameState;
+
+/// A draw offered by a player, together with the number of moves played when it was made.
+/// The opponent can accept it until they play their next move.
+#[derive(Resource, Default)]
+pub struct DrawOffer(pub Option<(Player, usize)>);
+
+impl DrawOffer {
+    /// Whether the current player can accept an offer of their opponent.
+    pub fn is_open(&self, game_state: &GameState) -> bool {
+        self.0
+            == Some((
+                game_state.current_player().other(),
+                game_state.history().len().wrapping_sub(1),
+            ))
+    }
+
+    /// Whether `player` made an offer during the current turn.
+    pub fn made_by(&self, game_state: &GameState, player: Player) -> bool {
+        self.0 == Some((player, game_state.history().len()))
+    }
+}
+
+/// Requests on behalf of the current player, who needs to be human.
+#[derive(Event, Clone, Copy)]
+pub enum GameEndRequest {
+    Resign,
+    /// Accepts the draw offered by the opponent or offers one.
+    OfferDraw,
+}
+
+fn s_apply_game_end_requests(
+    mut game_end_requests: EventReader<GameEndRequest>,
+    controllers: Res<PlayerControllers>,
+    mut game_state: ResMut<GameState>,
+    current_player: Res<CurrentPlayer>,
+    mut draw_offer: ResMut<DrawOffer>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let player = current_player.player;
+    if !controllers.is_human(player) {
+        game_end_requests.clear();
+        return;
+    }
+
+    for request in game_end_requests.read() {
+        match request {
+            GameEndRequest::Resign => game_state.resign(player),
+            GameEndRequest::OfferDraw => match draw_offer.is_open(&game_state) {
+                true => game_state.agree_draw(),
+                false => draw_offer.0 = Some((player, game_state.history().len())),
+            },
+        }
+    }
+
+    if game_state.result().is_some() {
+        draw_offer.0 = None;
+        next_state.set(AppState::MoveFinished);
+    }
+}
+
+pub struct GameEndPlugin;
+
+impl Plugin for GameEndPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DrawOffer>();
+        app.add_event::<GameEndRequest>();
+        app.add_systems(
+            Update,
+            s_apply_game_end_requests
+                .run_if(on_event::<GameEndRequest>())
+                .run_if(in_state(AppState::Idle)),
+        );
+    }
+}
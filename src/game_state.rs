@@ -0,0 +1,1423 @@
+use crate::data::components::PlayerInventory;
+use crate::data::enums::InsectType::Queen;
+use crate::data::enums::{DrawReason, GameResult, InsectType, Player, WinReason};
+use crate::hex_coordinate::{HexCoordinate, Symmetry, ALL_DIRECTIONS};
+use crate::rule_set::RuleSet;
+use crate::rules;
+use crate::zobrist;
+use bevy::prelude::Resource;
+use bevy::utils::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Tile {
+    pub player: Player,
+    pub insect: InsectType,
+    /// Counts the pieces of the same player and insect in the order they were placed, starting at 1.
+    pub ordinal: u8,
+}
+
+/// All tiles in the hive, stored as stacks ordered from bottom to top.
+///
+/// Searches move tiles back and forth many times, so the board is laid out to make that cheap:
+/// the topmost tile of every position lives in a grid that wraps around at its edges, and only
+/// the few tiles with others on top are kept in a list. Once the grid is large enough for the
+/// hive, [`Board::push`] and [`Board::pop`] don't allocate anymore.
+///
+/// The positions are kept in order, so everything derived from them, like the order of the legal
+/// moves, is the same in every run of the program.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "Stacks", into = "Stacks")]
+pub struct Board {
+    /// `size` times `size` cells, see [`Board::cell`].
+    cells: Vec<Cell>,
+    size: usize,
+    /// Every occupied position, in order.
+    positions: Vec<HexCoordinate>,
+    /// The tiles that have others on top, the lower ones of each stack first.
+    buried: Vec<(HexCoordinate, Tile)>,
+}
+
+/// The grid is wider than any hive of up to this many tiles, so no two of their positions share
+/// a cell. Larger hives make it grow.
+const INITIAL_GRID_SIZE: usize = 32;
+
+#[derive(Clone, Copy, Default)]
+struct Cell {
+    /// The position the cell holds the top tile of, only meaningful while `height` isn't 0.
+    position: HexCoordinate,
+    top: Option<Tile>,
+    height: u32,
+}
+
+/// The stacks of a [`Board`] by position, which is how boards are saved.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Board")]
+struct Stacks(BTreeMap<HexCoordinate, Vec<Tile>>);
+
+impl From<Stacks> for Board {
+    fn from(stacks: Stacks) -> Self {
+        let mut board = Board::default();
+        for (position, stack) in stacks.0 {
+            for tile in stack {
+                board.push(position, tile);
+            }
+        }
+        board
+    }
+}
+
+impl From<Board> for Stacks {
+    fn from(board: Board) -> Self {
+        Stacks(
+            board
+                .stacks()
+                .map(|(position, stack)| (*position, stack.collect()))
+                .collect(),
+        )
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board {
+            cells: vec![Cell::default(); INITIAL_GRID_SIZE * INITIAL_GRID_SIZE],
+            size: INITIAL_GRID_SIZE,
+            positions: vec![],
+            buried: vec![],
+        }
+    }
+}
+
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.positions == other.positions
+            && self
+                .positions
+                .iter()
+                .all(|position| self.get_stack(position).eq(other.get_stack(position)))
+    }
+}
+
+impl std::fmt::Debug for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(
+                self.stacks()
+                    .map(|(position, stack)| (position, stack.collect::<Vec<_>>())),
+            )
+            .finish()
+    }
+}
+
+impl Board {
+    pub fn contains(&self, position: &HexCoordinate) -> bool {
+        self.get_cell(position).is_some()
+    }
+
+    pub fn get_top(&self, position: &HexCoordinate) -> Option<&Tile> {
+        self.get_cell(position)?.top.as_ref()
+    }
+
+    /// The tiles at `position`, from bottom to top.
+    pub fn get_stack(
+        &self,
+        position: &HexCoordinate,
+    ) -> impl DoubleEndedIterator<Item = Tile> + '_ {
+        let position = *position;
+        self.buried
+            .iter()
+            .filter(move |(buried, _)| *buried == position)
+            .map(|(_, tile)| *tile)
+            .chain(self.get_top(&position).copied())
+    }
+
+    pub fn height(&self, position: &HexCoordinate) -> usize {
+        self.get_cell(position)
+            .map_or(0, |cell| cell.height as usize)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = &HexCoordinate> {
+        self.positions.iter()
+    }
+
+    pub fn stacks(
+        &self,
+    ) -> impl Iterator<Item = (&HexCoordinate, impl DoubleEndedIterator<Item = Tile> + '_)> {
+        self.positions
+            .iter()
+            .map(|position| (position, self.get_stack(position)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn push(&mut self, position: HexCoordinate, tile: Tile) {
+        let index = self.cell(&position);
+        let cell = self.cells[index];
+        if cell.height == 0 {
+            let insert_at = self.positions.binary_search(&position).unwrap_err();
+            self.positions.insert(insert_at, position);
+        } else if cell.position != position {
+            //another position of the hive has the same cell
+            self.grow();
+            return self.push(position, tile);
+        } else {
+            self.buried.push((position, cell.top.unwrap()));
+        }
+
+        self.cells[index] = Cell {
+            position,
+            top: Some(tile),
+            height: cell.height + 1,
+        };
+    }
+
+    pub fn pop(&mut self, position: &HexCoordinate) -> Option<Tile> {
+        self.get_cell(position)?;
+        let index = self.cell(position);
+        let cell = &mut self.cells[index];
+        let tile = cell.top.take();
+        cell.height -= 1;
+
+        if cell.height == 0 {
+            let remove_at = self.positions.binary_search(position).unwrap();
+            self.positions.remove(remove_at);
+        } else {
+            let buried = self
+                .buried
+                .iter()
+                .rposition(|(buried, _)| buried == position)
+                .unwrap();
+            cell.top = Some(self.buried.remove(buried).1);
+        }
+        tile
+    }
+
+    /// The board as the topmost tile at `position` sees it while it moves.
+    pub fn lifted(&self, position: HexCoordinate) -> LiftedBoard<'_> {
+        LiftedBoard {
+            board: self,
+            position,
+        }
+    }
+
+    /// The index of the cell of `position`. The grid wraps around, so positions that are at least
+    /// `size` apart share cells, but at most one of them is ever occupied.
+    fn cell(&self, position: &HexCoordinate) -> usize {
+        let (x, y) = position.axial();
+        let mask = self.size - 1;
+        (y as usize & mask) * self.size + (x as usize & mask)
+    }
+
+    fn get_cell(&self, position: &HexCoordinate) -> Option<&Cell> {
+        let cell = &self.cells[self.cell(position)];
+        (cell.height > 0 && cell.position == *position).then_some(cell)
+    }
+
+    /// Doubles the size of the grid until every occupied position has a cell of its own.
+    fn grow(&mut self) {
+        let occupied: Vec<Cell> = self
+            .cells
+            .iter()
+            .filter(|cell| cell.height > 0)
+            .copied()
+            .collect();
+
+        loop {
+            self.size *= 2;
+            self.cells = vec![Cell::default(); self.size * self.size];
+            let separated = occupied.iter().all(|cell| {
+                let index = self.cell(&cell.position);
+                let free = self.cells[index].height == 0;
+                self.cells[index] = *cell;
+                free
+            });
+            if separated {
+                return;
+            }
+        }
+    }
+
+    /// The same hive after applying `symmetry` to every position, moved so that its smallest
+    /// position is the origin.
+    pub fn normalized(&self, symmetry: &Symmetry) -> Board {
+        let mut board = Board::default();
+        let positions = self
+            .positions()
+            .map(|position| position.transformed(symmetry));
+        let Some(anchor) = positions.min() else {
+            return board;
+        };
+
+        for (position, stack) in self.stacks() {
+            let position = position.transformed(symmetry).relative_to(&anchor);
+            for tile in stack {
+                board.push(position, tile);
+            }
+        }
+        board
+    }
+
+    /// The same board for all hives that only differ by where they are or by rotating or
+    /// mirroring them, which is the version of them with the smallest [`zobrist`] key.
+    pub fn canonical(&self) -> Board {
+        Symmetry::all()
+            .map(|symmetry| self.normalized(&symmetry))
+            .min_by_key(zobrist::board_key)
+            .unwrap()
+    }
+
+    /// Returns a copy of the board with the topmost tile at `without` removed. Moves are found
+    /// on the [`Board::lifted`] board instead, which doesn't copy anything.
+    pub fn get_without(&self, without: &HexCoordinate) -> Board {
+        let mut board = self.clone();
+        board.pop(without);
+        board
+    }
+
+    pub fn get_surrounding_slidable_tiles(
+        &self,
+        new_position: HexCoordinate,
+        ignore: &[HexCoordinate],
+    ) -> Vec<HexCoordinate> {
+        surrounding_slidable_tiles(|position| self.contains(position), new_position, ignore)
+    }
+}
+
+/// A [`Board`] with the topmost tile at one position picked up, which is the hive a tile moves
+/// around. It answers the queries the moves are found with, without copying the board.
+#[derive(Clone, Copy)]
+pub struct LiftedBoard<'a> {
+    board: &'a Board,
+    position: HexCoordinate,
+}
+
+impl LiftedBoard<'_> {
+    pub fn contains(&self, position: &HexCoordinate) -> bool {
+        self.height(position) > 0
+    }
+
+    pub fn height(&self, position: &HexCoordinate) -> usize {
+        let height = self.board.height(position);
+        match *position == self.position {
+            true => height.saturating_sub(1),
+            false => height,
+        }
+    }
+
+    pub fn get_surrounding_slidable_tiles(
+        &self,
+        new_position: HexCoordinate,
+        ignore: &[HexCoordinate],
+    ) -> Vec<HexCoordinate> {
+        surrounding_slidable_tiles(|position| self.contains(position), new_position, ignore)
+    }
+}
+
+/// The empty positions next to `new_position` a tile can slide to, which are those it reaches
+/// by passing exactly one of the two positions next to both, so it neither squeezes through a
+/// gap nor leaves the hive.
+fn surrounding_slidable_tiles(
+    contains: impl Fn(&HexCoordinate) -> bool,
+    new_position: HexCoordinate,
+    ignore: &[HexCoordinate],
+) -> Vec<HexCoordinate> {
+    let mut valid_positions = vec![];
+
+    for direction in ALL_DIRECTIONS {
+        let relative_position = new_position.get_relative(direction);
+        if contains(&relative_position) {
+            continue;
+        }
+
+        if ignore.contains(&relative_position) {
+            continue;
+        }
+
+        let sides = direction.get_adjacent_directions();
+
+        let mut filled_space_count = 0;
+        for side in sides {
+            if contains(&new_position.get_relative(side)) {
+                filled_space_count += 1;
+            }
+        }
+        if filled_space_count == 1 {
+            valid_positions.push(relative_position);
+        }
+    }
+
+    valid_positions
+}
+
+/// A single turn of a player. `Move` always moves the topmost tile of the stack at `from`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Move {
+    Place {
+        insect: InsectType,
+        at: HexCoordinate,
+    },
+    Move {
+        from: HexCoordinate,
+        to: HexCoordinate,
+    },
+    Pass,
+}
+
+/// What [`GameState::undo`] took back.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Undone {
+    Move(Move),
+    /// A resignation or an agreed draw, which ended the game without a move.
+    DeclaredResult(GameResult),
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct IllegalMove(pub Move);
+
+impl std::fmt::Display for IllegalMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "illegal move {:?}", self.0)
+    }
+}
+
+impl std::error::Error for IllegalMove {}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct PlayedMove {
+    mv: Move,
+    /// Where a placed piece was taken from, so undo can restore the inventory order.
+    inventory_index: usize,
+    /// Identifies the position after the move to find repetitions. Not saved, because loaded
+    /// games are replayed, see [`GameState::replayed`].
+    #[serde(skip)]
+    position: Option<u64>,
+    /// The [`rules::get_pinned_positions`] of the position after the move. They are found the
+    /// first time they are needed and kept while later moves are played and taken back.
+    #[serde(skip)]
+    pinned: OnceLock<HashSet<HexCoordinate>>,
+}
+
+/// The complete state of a game of Hive, independent of any rendering or ECS.
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct GameState {
+    board: Board,
+    player1_inventory: PlayerInventory,
+    player2_inventory: PlayerInventory,
+    current_player: Player,
+    history: Vec<PlayedMove>,
+    /// Games saved before rule sets existed were played with the default rules.
+    #[serde(default)]
+    rules: RuleSet,
+    /// A resignation or an agreed draw, which end the game without a move.
+    #[serde(default)]
+    declared_result: Option<GameResult>,
+    /// The [`zobrist`] hash of the position, updated by every move. Not saved, because
+    /// [`GameState::replayed`] hashes the position again.
+    #[serde(skip)]
+    hash: u64,
+    /// The [`rules::get_pinned_positions`] before the first move, see [`PlayedMove::pinned`].
+    #[serde(skip)]
+    initial_pinned: OnceLock<HashSet<HexCoordinate>>,
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        GameState::new()
+    }
+}
+
+impl GameState {
+    pub fn new() -> GameState {
+        GameState::with_rules(RuleSet::default())
+    }
+
+    /// Starts a new game played with `rules`.
+    pub fn with_rules(rules: RuleSet) -> GameState {
+        let mut game_state = GameState {
+            board: Board::default(),
+            player1_inventory: rules.inventory(),
+            player2_inventory: rules.inventory(),
+            current_player: Player::Player1,
+            history: vec![],
+            rules,
+            declared_result: None,
+            hash: 0,
+            initial_pinned: OnceLock::new(),
+        };
+        game_state.hash = zobrist::hash(&game_state);
+        game_state
+    }
+
+    /// Replays `moves` from the start of a new game, validating each of them.
+    pub fn from_moves(moves: impl IntoIterator<Item = Move>) -> Result<GameState, IllegalMove> {
+        let mut game_state = GameState::new();
+        for mv in moves {
+            game_state.try_apply(mv)?;
+        }
+        Ok(game_state)
+    }
+
+    /// Plays the moves of this game again from the start of a new game with the same rules,
+    /// which restores what isn't saved. Fails on the first move that isn't legal, so nothing
+    /// but the moves and the rules of a loaded game has to be trusted.
+    pub fn replayed(&self) -> Result<GameState, IllegalMove> {
+        let mut game_state = GameState::with_rules(self.rules.clone());
+        for mv in self.history() {
+            game_state.try_apply(mv)?;
+        }
+        if game_state.result().is_none() {
+            game_state.declared_result = self.declared_result;
+        }
+        Ok(game_state)
+    }
+
+    /// The state this game started from, before any move was played.
+    pub fn initial(&self) -> GameState {
+        let mut game_state = self.clone();
+        while game_state.undo().is_some() {}
+        game_state.hash = zobrist::hash(&game_state);
+        game_state
+    }
+
+    /// The tile the current player would put into the hive when placing `insect`.
+    pub fn tile_to_place(&self, insect: InsectType) -> Tile {
+        let player = self.current_player;
+        let ordinal = self
+            .board
+            .stacks()
+            .flat_map(|(_, stack)| stack)
+            .filter(|tile| tile.player == player && tile.insect == insect)
+            .count() as u8
+            + 1;
+
+        Tile {
+            player,
+            insect,
+            ordinal,
+        }
+    }
+
+    /// Finds the position of a specific tile, e.g. to resolve a move written in notation.
+    pub fn find_tile(&self, tile: &Tile) -> Option<HexCoordinate> {
+        self.board
+            .stacks()
+            .find_map(|(position, mut stack)| stack.any(|t| t == *tile).then_some(*position))
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    pub fn current_player(&self) -> Player {
+        self.current_player
+    }
+
+    /// Identifies the tiles, the pieces left to place and whose turn it is, see [`zobrist`].
+    pub fn zobrist_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Every move played so far, oldest first.
+    pub fn history(&self) -> impl DoubleEndedIterator<Item = Move> + ExactSizeIterator + '_ {
+        self.history.iter().map(|played| played.mv)
+    }
+
+    pub fn inventory(&self, player: Player) -> &PlayerInventory {
+        match player {
+            Player::Player1 => &self.player1_inventory,
+            Player::Player2 => &self.player2_inventory,
+        }
+    }
+
+    fn inventory_mut(&mut self, player: Player) -> &mut PlayerInventory {
+        match player {
+            Player::Player1 => &mut self.player1_inventory,
+            Player::Player2 => &mut self.player2_inventory,
+        }
+    }
+
+    /// The pieces the current player may take from their inventory this turn.
+    pub fn pieces_to_place(&self) -> Vec<InsectType> {
+        let inventory = self.inventory(self.current_player);
+        self.rules
+            .allowed_placements(&inventory.pieces, inventory.moves_played)
+    }
+
+    pub fn placements(&self) -> Vec<HexCoordinate> {
+        if self.board.is_empty() {
+            return vec![HexCoordinate::origin()];
+        }
+
+        let player_has_tile_in_game = self
+            .board
+            .stacks()
+            .flat_map(|(_, stack)| stack)
+            .any(|tile| tile.player == self.current_player);
+
+        rules::get_moves_for_new_piece(&self.board, self.current_player, !player_has_tile_in_game)
+    }
+
+    /// Where the current player may move the top tile at `from`. This is usually one of their
+    /// own tiles, but their pillbugs can also move tiles of the opponent.
+    pub fn moves_from(&self, from: HexCoordinate) -> Vec<HexCoordinate> {
+        let Some(tile) = self.board.get_top(&from) else {
+            return vec![];
+        };
+
+        //nothing can be moved before the own queen is placed
+        if !self.queen_placed(self.current_player) {
+            return vec![];
+        }
+
+        //the tile moved last turn can neither move nor be moved, and can't use its ability
+        let stunned = self.last_moved();
+        if stunned == Some(from) {
+            return vec![];
+        }
+
+        let pinned = self.pinned_positions();
+        let mut moves = match tile.player == self.current_player {
+            true => rules::get_moves_for_tile_with_pinned(&self.board, from, pinned),
+            false => vec![],
+        };
+
+        for pillbug in ALL_DIRECTIONS.map(|direction| from.get_relative(direction)) {
+            //a mosquito next to a pillbug can use its ability as well
+            let is_own_pillbug = match self.board.get_top(&pillbug) {
+                Some(tile)
+                    if self.board.height(&pillbug) == 1 && tile.player == self.current_player =>
+                {
+                    match tile.insect {
+                        InsectType::Pillbug => true,
+                        InsectType::Mosquito => rules::get_copied_insects(&self.board, pillbug)
+                            .contains(&InsectType::Pillbug),
+                        _ => false,
+                    }
+                }
+                _ => false,
+            };
+            if !is_own_pillbug || stunned == Some(pillbug) {
+                continue;
+            }
+
+            for to in rules::get_moves_for_pillbug_ability(&self.board, pillbug, from, pinned) {
+                if !moves.contains(&to) {
+                    moves.push(to);
+                }
+            }
+        }
+
+        moves
+    }
+
+    /// The [`rules::get_pinned_positions`] of the board. Each position of the game searches
+    /// for them once, and keeps them while later moves are played and taken back, so a search
+    /// returning to it doesn't look again.
+    pub fn pinned_positions(&self) -> &HashSet<HexCoordinate> {
+        let pinned = match self.history.last() {
+            Some(played) => &played.pinned,
+            None => &self.initial_pinned,
+        };
+        pinned.get_or_init(|| rules::get_pinned_positions(&self.board))
+    }
+
+    /// Whether `player` has no queen left to place, which is also true for rule sets without one.
+    pub fn queen_placed(&self, player: Player) -> bool {
+        !self.inventory(player).pieces.contains(&Queen)
+    }
+
+    /// The position of the tile moved by the last move, if it wasn't a placement or a pass.
+    fn last_moved(&self) -> Option<HexCoordinate> {
+        match self.history.last()?.mv {
+            Move::Move { to, .. } => Some(to),
+            _ => None,
+        }
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if self.result().is_some() {
+            return vec![];
+        }
+
+        let mut moves = vec![];
+
+        let mut insects = vec![];
+        for insect in self.pieces_to_place() {
+            if !insects.contains(&insect) {
+                insects.push(insect);
+            }
+        }
+
+        if !insects.is_empty() {
+            let placements = self.placements();
+            for insect in insects {
+                for at in &placements {
+                    moves.push(Move::Place { insect, at: *at });
+                }
+            }
+        }
+
+        for from in self.board.positions() {
+            for to in self.moves_from(*from) {
+                moves.push(Move::Move { from: *from, to });
+            }
+        }
+
+        moves
+    }
+
+    /// Whether the game goes on but the current player can neither place nor move anything.
+    pub fn must_pass(&self) -> bool {
+        self.result().is_none() && self.legal_moves().is_empty()
+    }
+
+    /// Counts the positions reached after exactly `depth` more moves, where a forced pass is a
+    /// move as well and finished games don't go on. Any change of the rules that alters which
+    /// moves are legal shows up in these numbers.
+    pub fn perft(&mut self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        if self.result().is_some() {
+            return 0;
+        }
+
+        let mut moves = self.legal_moves();
+        if moves.is_empty() {
+            moves.push(Move::Pass);
+        }
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        moves
+            .into_iter()
+            .map(|mv| {
+                self.apply(mv);
+                let count = self.perft(depth - 1);
+                self.undo();
+                count
+            })
+            .sum()
+    }
+
+    /// A pass is only legal if the current player has nothing else to do.
+    pub fn is_legal(&self, mv: &Move) -> bool {
+        match mv {
+            Move::Pass => self.must_pass(),
+            _ => self.legal_moves().contains(mv),
+        }
+    }
+
+    pub fn try_apply(&mut self, mv: Move) -> Result<(), IllegalMove> {
+        if !self.is_legal(&mv) {
+            return Err(IllegalMove(mv));
+        }
+        self.apply(mv);
+        Ok(())
+    }
+
+    /// Plays `mv` for the current player and passes the turn to the opponent.
+    /// The move is expected to be legal, use [`GameState::try_apply`] for unchecked input.
+    pub fn apply(&mut self, mv: Move) {
+        let player = self.current_player;
+        let mut inventory_index = 0;
+
+        match mv {
+            Move::Place { insect, at } => {
+                let inventory = self.inventory_mut(player);
+                inventory_index = inventory
+                    .pieces
+                    .iter()
+                    .position(|i| *i == insect)
+                    .expect("Placed piece needs to be in the inventory");
+                inventory.pieces.remove(inventory_index);
+                self.toggle_hand_count(player, insect);
+
+                let tile = self.tile_to_place(insect);
+                self.push_tile(at, tile);
+            }
+            Move::Move { from, to } => {
+                let tile = self.pop_tile(from);
+                self.push_tile(to, tile);
+            }
+            Move::Pass => {}
+        }
+
+        self.inventory_mut(player).moves_played += 1;
+        self.current_player = player.other();
+        self.hash ^= zobrist::turn_key(player) ^ zobrist::turn_key(player.other());
+        self.history.push(PlayedMove {
+            mv,
+            inventory_index,
+            position: None,
+            pinned: OnceLock::new(),
+        });
+        let position = self.position_key();
+        self.history.last_mut().unwrap().position = Some(position);
+    }
+
+    /// `player` gives up, which ends the game right away.
+    pub fn resign(&mut self, player: Player) {
+        if self.result().is_none() {
+            self.declared_result = Some(GameResult::PlayerWon(
+                player.other(),
+                WinReason::Resignation,
+            ));
+        }
+    }
+
+    /// Ends the game in a draw both players agreed on.
+    pub fn agree_draw(&mut self) {
+        if self.result().is_none() {
+            self.declared_result = Some(GameResult::Draw(DrawReason::Agreement));
+        }
+    }
+
+    /// Takes back the last step of the game and returns it, restoring the exact previous state.
+    /// A resignation or an agreed draw is a step of its own, taken back before the move before it.
+    pub fn undo(&mut self) -> Option<Undone> {
+        if let Some(result) = self.declared_result.take() {
+            return Some(Undone::DeclaredResult(result));
+        }
+
+        let PlayedMove {
+            mv,
+            inventory_index,
+            ..
+        } = self.history.pop()?;
+        let player = self.current_player.other();
+
+        match mv {
+            Move::Place { insect, at } => {
+                self.pop_tile(at);
+                self.toggle_hand_count(player, insect);
+                self.inventory_mut(player)
+                    .pieces
+                    .insert(inventory_index, insect);
+            }
+            Move::Move { from, to } => {
+                let tile = self.pop_tile(to);
+                self.push_tile(from, tile);
+            }
+            Move::Pass => {}
+        }
+
+        self.inventory_mut(player).moves_played -= 1;
+        self.hash ^= zobrist::turn_key(self.current_player) ^ zobrist::turn_key(player);
+        self.current_player = player;
+        Some(Undone::Move(mv))
+    }
+
+    fn push_tile(&mut self, position: HexCoordinate, tile: Tile) {
+        self.hash ^= zobrist::tile_key(&tile, position, self.board.height(&position));
+        self.board.push(position, tile);
+    }
+
+    fn pop_tile(&mut self, position: HexCoordinate) -> Tile {
+        let tile = self
+            .board
+            .pop(&position)
+            .expect("Moved or placed piece needs to be on the board");
+        self.hash ^= zobrist::tile_key(&tile, position, self.board.height(&position));
+        tile
+    }
+
+    /// Updates the hash for a piece of `insect` taken from or put back into the hand of `player`.
+    /// Needs to be called while the hand has the fewer pieces.
+    fn toggle_hand_count(&mut self, player: Player, insect: InsectType) {
+        let fewer = self
+            .inventory(player)
+            .pieces
+            .iter()
+            .filter(|i| **i == insect)
+            .count();
+        self.hash ^= zobrist::hand_key(player, insect, fewer);
+        self.hash ^= zobrist::hand_key(player, insect, fewer + 1);
+    }
+
+    pub fn result(&self) -> Option<GameResult> {
+        if self.declared_result.is_some() {
+            return self.declared_result;
+        }
+
+        let mut players_that_lost = vec![];
+
+        for (hex, stack) in self.board.stacks() {
+            for tile in stack {
+                if tile.insect != Queen {
+                    continue;
+                }
+
+                let surrounded = ALL_DIRECTIONS
+                    .iter()
+                    .map(|direction| hex.get_relative(direction))
+                    .all(|relative_position| self.board.contains(&relative_position));
+
+                if surrounded {
+                    players_that_lost.push(tile.player);
+                }
+            }
+        }
+
+        match players_that_lost.len() {
+            0 if self.both_players_stuck() => Some(GameResult::Draw(DrawReason::NoLegalMoves)),
+            0 if self.repetitions() >= 3 => Some(GameResult::Draw(DrawReason::Repetition)),
+            0 if self
+                .rules
+                .move_limit
+                .is_some_and(|limit| self.history.len() >= 2 * limit as usize) =>
+            {
+                Some(GameResult::Draw(DrawReason::MoveLimit))
+            }
+            0 => None,
+            1 => Some(GameResult::PlayerWon(
+                players_that_lost[0].other(),
+                WinReason::QueenSurrounded,
+            )),
+            _ => Some(GameResult::Draw(DrawReason::QueensSurrounded)),
+        }
+    }
+
+    /// How often the current position came up in this game, including now.
+    fn repetitions(&self) -> usize {
+        let Some(current) = self.history.last().and_then(|played| played.position) else {
+            return 0;
+        };
+        self.history
+            .iter()
+            .filter(|played| played.position == Some(current))
+            .count()
+    }
+
+    /// Identifies the position together with which tile is stunned. Equal positions always get
+    /// the same key, different ones almost never do.
+    fn position_key(&self) -> u64 {
+        self.hash ^ zobrist::stunned_key(self.last_moved())
+    }
+
+    /// Two passes in a row mean that neither player can do anything anymore, unless the first
+    /// one was only forced because a pillbug stunned the last tile that could move.
+    fn both_players_stuck(&self) -> bool {
+        let mut last_moves = self.history().rev();
+        matches!(
+            (last_moves.next(), last_moves.next(), last_moves.next()),
+            (
+                Some(Move::Pass),
+                Some(Move::Pass),
+                None | Some(Move::Pass | Move::Place { .. })
+            )
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex_coordinate::HexDirection;
+    use crate::notation::{format_move, play, play_moves, tile};
+
+    /// The moves of the tile called `name` in the notation, sorted.
+    fn moves_of(game_state: &GameState, name: &str) -> Vec<String> {
+        let from = game_state.find_tile(&tile(name)).unwrap();
+        let mut moves: Vec<_> = game_state
+            .moves_from(from)
+            .into_iter()
+            .map(|to| format_move(game_state, &Move::Move { from, to }))
+            .collect();
+        moves.sort();
+        moves
+    }
+
+    /// White's pillbug in the middle, touching bA1, bQ and wQ.
+    const PILLBUG_POSITION: &str = "wP;bQ wP-;wQ /wP;bA1 bQ/;wA1 /wQ;bA1 wP/;wS1 -wQ;bS1 bQ\\";
+
+    #[test]
+    fn new_game_starts_with_white_placing_at_the_origin() {
+        let game_state = GameState::new();
+        assert!(game_state.board().is_empty());
+        assert_eq!(game_state.current_player(), Player::Player1);
+        assert_eq!(game_state.result(), None);
+        for player in [Player::Player1, Player::Player2] {
+            assert_eq!(
+                game_state.inventory(player).pieces,
+                RuleSet::default().inventory().pieces
+            );
+        }
+
+        let moves = game_state.legal_moves();
+        assert_eq!(moves.len(), 6);
+        assert!(moves.iter().all(|mv| matches!(
+            mv,
+            Move::Place { at, .. } if *at == HexCoordinate::origin()
+        )));
+    }
+
+    #[test]
+    fn applying_moves_changes_the_board_and_the_turn() {
+        let mut game_state = play("wQ;bQ wQ-;wA1 -wQ");
+        let white_queen = game_state.find_tile(&tile("wQ")).unwrap();
+        assert_eq!(white_queen, HexCoordinate::origin());
+        assert_eq!(
+            game_state.find_tile(&tile("bQ")),
+            Some(white_queen.get_relative(&HexDirection::Right))
+        );
+        assert_eq!(game_state.current_player(), Player::Player2);
+        assert_eq!(game_state.inventory(Player::Player1).moves_played, 2);
+        assert!(!game_state
+            .inventory(Player::Player1)
+            .pieces
+            .contains(&Queen));
+
+        let ant = game_state.find_tile(&tile("wA1")).unwrap();
+        play_moves(&mut game_state, "bA1 bQ-;wA1 wQ\\").unwrap();
+        assert_eq!(game_state.board().height(&ant), 0);
+        assert_eq!(
+            game_state.find_tile(&tile("wA1")),
+            Some(white_queen.get_relative(&HexDirection::DownRight))
+        );
+        assert_eq!(game_state.board().len(), 4);
+    }
+
+    /// Everything a move changes, to compare states before and after undoing it.
+    fn snapshot(game_state: &GameState) -> (Board, [Vec<InsectType>; 2], [u32; 2], Player, usize) {
+        let [white, black] = [Player::Player1, Player::Player2].map(|p| game_state.inventory(p));
+        (
+            game_state.board().clone(),
+            [white.pieces.clone(), black.pieces.clone()],
+            [white.moves_played, black.moves_played],
+            game_state.current_player(),
+            game_state.history().len(),
+        )
+    }
+
+    #[test]
+    fn undo_restores_the_state_before_each_move() {
+        //placements from the middle of the inventory, moves, and beetles climbing on and off
+        let moves = "wG1;bS1 wG1-;wQ -wG1;bQ bS1-;wB1 wQ/;bB1 bQ\\;wB1 wQ;bB1 bQ;wB1 wG1";
+        let mut game_state = GameState::new();
+        let mut before = vec![];
+        for move_string in moves.split(';') {
+            before.push(snapshot(&game_state));
+            play_moves(&mut game_state, move_string).unwrap();
+        }
+
+        while let Some(snapshot_before) = before.pop() {
+            let mv = game_state.history().last();
+            assert_eq!(game_state.undo(), mv.map(Undone::Move));
+            assert_eq!(snapshot(&game_state), snapshot_before, "undoing {mv:?}");
+        }
+        assert_eq!(game_state.undo(), None);
+    }
+
+    #[test]
+    fn illegal_moves_are_rejected_without_changes() {
+        let mut game_state = play("wQ;bQ wQ-");
+        let before = snapshot(&game_state);
+        let queen = game_state.find_tile(&tile("wQ")).unwrap();
+
+        for mv in [
+            //the queen is already placed
+            Move::Place {
+                insect: Queen,
+                at: queen.get_relative(&HexDirection::Left),
+            },
+            //next to a black tile
+            Move::Place {
+                insect: InsectType::Ant,
+                at: queen
+                    .get_relative(&HexDirection::Right)
+                    .get_relative(&HexDirection::Right),
+            },
+            //the black queen can't be moved by white
+            Move::Move {
+                from: queen.get_relative(&HexDirection::Right),
+                to: queen.get_relative(&HexDirection::UpRight),
+            },
+            Move::Pass,
+        ] {
+            assert_eq!(game_state.try_apply(mv), Err(IllegalMove(mv)));
+            assert_eq!(snapshot(&game_state), before, "{mv:?}");
+        }
+
+        let mv = Move::Move {
+            from: queen,
+            to: queen.get_relative(&HexDirection::UpRight),
+        };
+        assert_eq!(game_state.try_apply(mv), Ok(()));
+        assert_eq!(game_state.history().last(), Some(mv));
+    }
+
+    #[test]
+    fn pillbug_moves_adjacent_tiles() {
+        let game_state = play(PILLBUG_POSITION);
+
+        assert_eq!(
+            moves_of(&game_state, "bA1"),
+            ["bA1 -wP", "bA1 /bQ", "bA1 \\wP"]
+        );
+        //moving bQ or wQ would split the hive
+        assert!(moves_of(&game_state, "bQ").is_empty());
+        assert!(moves_of(&game_state, "wQ").is_empty());
+    }
+
+    #[test]
+    fn pillbug_moves_like_a_queen() {
+        let game_state = play(PILLBUG_POSITION);
+        let pillbug = game_state.find_tile(&tile("wP")).unwrap();
+
+        let mut board = game_state.board().clone();
+        let mut queen = board.pop(&pillbug).unwrap();
+        queen.insect = Queen;
+        board.push(pillbug, queen);
+
+        assert_eq!(
+            game_state.moves_from(pillbug),
+            rules::get_moves_for_tile(&board, pillbug)
+        );
+    }
+
+    #[test]
+    fn pillbug_cannot_move_the_last_moved_tile() {
+        //bA1 was moved next to the pillbug by the last move
+        let game_state = play("wP;bQ wP-;wQ /wP;bA1 bQ/;wA1 /wQ;bA1 wP/");
+        assert!(moves_of(&game_state, "bA1").is_empty());
+    }
+
+    #[test]
+    fn tiles_moved_by_a_pillbug_are_stunned() {
+        let mut game_state = play(PILLBUG_POSITION);
+        play_moves(&mut game_state, "bA1 -wP").unwrap();
+        assert!(moves_of(&game_state, "bA1").is_empty());
+
+        //only for the next turn
+        play_moves(&mut game_state, "bG1 bQ/").unwrap();
+        assert!(!moves_of(&game_state, "bA1").is_empty());
+    }
+
+    #[test]
+    fn mosquito_uses_the_ability_of_a_touching_pillbug() {
+        let mut rules = RuleSet::default();
+        rules.set_count(InsectType::Mosquito, 1);
+        let mut game_state = GameState::with_rules(rules);
+        play_moves(&mut game_state, "wM;bP wM-;wQ -wM;bQ bP-").unwrap();
+
+        //wQ can only slide to two places, but the mosquito can put it down in two more
+        assert_eq!(moves_of(&game_state, "wQ").len(), 4);
+    }
+
+    #[test]
+    fn stunned_pillbug_cannot_use_its_ability() {
+        //black's pillbug just moved wP next to wG1
+        let game_state =
+            play("wQ;bP wQ-;wP -wQ;bQ bP-;wP \\wQ;bA1 bQ-;wP wQ/;bS1 bQ\\;wG1 /wQ;wP /bP");
+        let grasshopper = game_state.find_tile(&tile("wG1")).unwrap();
+
+        assert!(moves_of(&game_state, "wP").is_empty());
+        assert_eq!(
+            game_state.moves_from(grasshopper),
+            rules::get_moves_for_tile(game_state.board(), grasshopper)
+        );
+    }
+
+    #[test]
+    fn placements_follow_the_rule_set() {
+        let mut game_state = GameState::with_rules(RuleSet {
+            no_queen_first: true,
+            ..RuleSet::default()
+        });
+        assert!(!game_state.pieces_to_place().contains(&Queen));
+
+        //the default deadline is the third own turn
+        play_moves(&mut game_state, "wA1;bA1 -wA1;wS1 wA1-;bQ -bA1").unwrap();
+        assert_eq!(game_state.pieces_to_place(), [Queen]);
+        play_moves(&mut game_state, "wQ wS1-").unwrap();
+        assert!(game_state.pieces_to_place().len() > 1);
+    }
+
+    #[test]
+    fn nothing_moves_before_the_queen_is_placed() {
+        //black has placed its queen, white has not
+        let mut game_state = play("wA1;bQ -wA1;wG1 wA1-;bA1 -bQ");
+        assert!(!game_state.queen_placed(Player::Player1));
+        assert!(moves_of(&game_state, "wG1").is_empty());
+        assert!(moves_of(&game_state, "wA1").is_empty());
+        assert!(game_state
+            .legal_moves()
+            .iter()
+            .all(|mv| matches!(mv, Move::Place { .. })));
+
+        play_moves(&mut game_state, "wQ wG1\\;bA1 bQ/").unwrap();
+        assert!(!moves_of(&game_state, "wQ").is_empty());
+    }
+
+    #[test]
+    fn pillbug_cannot_move_tiles_before_the_queen_is_placed() {
+        //bA1 walked next to white's pillbug, which takes a later queen deadline to set up
+        let mut game_state = GameState::with_rules(RuleSet {
+            queen_deadline: 5,
+            ..RuleSet::default()
+        });
+        let moves = "wP;bQ wP-;wA1 -wP;bA1 bQ-;wA2 -wA1;bA1 wP/;wA3 -wA2;bG1 bQ-";
+        play_moves(&mut game_state, moves).unwrap();
+        assert!(moves_of(&game_state, "bA1").is_empty());
+
+        play_moves(&mut game_state, "wQ -wA3;bG2 bG1-").unwrap();
+        assert!(!moves_of(&game_state, "bA1").is_empty());
+    }
+
+    #[test]
+    fn passing_only_ends_the_game_if_both_players_are_stuck() {
+        let mut game_state = GameState::new();
+        game_state.player2_inventory.pieces.clear();
+        play_moves(&mut game_state, "wQ").unwrap();
+        assert!(game_state.must_pass());
+
+        game_state.try_apply(Move::Pass).unwrap();
+        assert_eq!(game_state.result(), None);
+        assert!(!game_state.must_pass());
+
+        let mut game_state = GameState::with_rules(RuleSet {
+            pieces: vec![],
+            ..RuleSet::default()
+        });
+        game_state.try_apply(Move::Pass).unwrap();
+        assert_eq!(game_state.result(), None);
+        game_state.try_apply(Move::Pass).unwrap();
+        assert_eq!(
+            game_state.result(),
+            Some(GameResult::Draw(DrawReason::NoLegalMoves))
+        );
+    }
+
+    #[test]
+    fn third_repetition_is_a_draw() {
+        let mut game_state = play("wQ;bQ wQ-;wA1 -wQ;bA1 bQ-");
+        let white = game_state.find_tile(&tile("wA1")).unwrap();
+        let black = game_state.find_tile(&tile("bA1")).unwrap();
+
+        //both ants walk away and back, the position after the first step repeats each round
+        let white_to = game_state.moves_from(white)[0];
+        let walk_away = Move::Move {
+            from: white,
+            to: white_to,
+        };
+        game_state.apply(walk_away);
+        let black_to = game_state.moves_from(black)[0];
+        game_state.undo();
+
+        let round = [
+            walk_away,
+            Move::Move {
+                from: black,
+                to: black_to,
+            },
+            Move::Move {
+                from: white_to,
+                to: white,
+            },
+            Move::Move {
+                from: black_to,
+                to: black,
+            },
+        ];
+        for mv in round.iter().chain(&round) {
+            game_state.try_apply(*mv).unwrap();
+            assert_eq!(game_state.result(), None);
+        }
+
+        game_state.try_apply(walk_away).unwrap();
+        assert_eq!(
+            game_state.result(),
+            Some(GameResult::Draw(DrawReason::Repetition))
+        );
+        game_state.undo();
+        assert_eq!(game_state.result(), None);
+    }
+
+    #[test]
+    fn move_limit_is_a_draw() {
+        let mut game_state = GameState::with_rules(RuleSet {
+            move_limit: Some(2),
+            ..RuleSet::default()
+        });
+        play_moves(&mut game_state, "wQ;bQ wQ-;wA1 -wQ").unwrap();
+        assert_eq!(game_state.result(), None);
+
+        play_moves(&mut game_state, "bA1 bQ-").unwrap();
+        assert_eq!(
+            game_state.result(),
+            Some(GameResult::Draw(DrawReason::MoveLimit))
+        );
+    }
+
+    #[test]
+    fn resigning_ends_the_game_until_undone() {
+        let mut game_state = play("wQ;bQ wQ-");
+        game_state.resign(Player::Player1);
+        let resignation = GameResult::PlayerWon(Player::Player2, WinReason::Resignation);
+        assert_eq!(game_state.result(), Some(resignation));
+        assert!(game_state.legal_moves().is_empty());
+
+        //only the resignation is taken back, not the move before it
+        let board = game_state.board().clone();
+        assert_eq!(game_state.undo(), Some(Undone::DeclaredResult(resignation)));
+        assert_eq!(game_state.result(), None);
+        assert_eq!(game_state.history().len(), 2);
+        assert_eq!(game_state.board(), &board);
+
+        game_state.agree_draw();
+        assert_eq!(
+            game_state.result(),
+            Some(GameResult::Draw(DrawReason::Agreement))
+        );
+        game_state.undo();
+        assert!(matches!(game_state.undo(), Some(Undone::Move(_))));
+        assert_eq!(game_state.history().len(), 1);
+    }
+
+    #[test]
+    fn results_declared_before_any_move_can_be_undone() {
+        let mut game_state = GameState::new();
+        game_state.agree_draw();
+        assert_eq!(
+            game_state.undo(),
+            Some(Undone::DeclaredResult(GameResult::Draw(
+                DrawReason::Agreement
+            )))
+        );
+        assert_eq!(game_state.result(), None);
+        assert_eq!(game_state.undo(), None);
+
+        game_state.resign(Player::Player1);
+        assert_eq!(game_state.initial().result(), None);
+        assert_eq!(game_state.replayed().unwrap().result(), game_state.result());
+    }
+
+    #[test]
+    fn perft_of_the_base_game() {
+        //the numbers other engines agree on
+        let mut game_state = GameState::with_rules(RuleSet::base_game());
+        let counts: Vec<_> = (0..=5).map(|depth| game_state.perft(depth)).collect();
+        assert_eq!(counts, [1, 4, 96, 1440, 21600, 516240]);
+    }
+
+    /// Positions with the default rules and their perft counts, starting at depth 1.
+    const PERFT_POSITIONS: &[(&str, &[u64])] = &[
+        //both beetles on top of the queens
+        ("wQ;bQ wQ-;wB1 -wQ;bB1 bQ-;wB1 wQ;bB1 bQ", &[21, 411, 12492]),
+        //a hole surrounded by five tiles, which sliding tiles can't enter
+        (
+            "wQ;bQ wQ-;wA1 -wQ;bA1 bQ-;wA2 /wA1;bA2 bA1\\;wA3 wA2\\;bA2 /bQ",
+            &[49, 2810],
+        ),
+        //both players have to place their queen now
+        ("wA1;bA1 wA1-;wG1 -wA1;bG1 bA1-", &[5, 25, 895]),
+        (PILLBUG_POSITION, &[47, 2196]),
+    ];
+
+    #[test]
+    fn perft_positions() {
+        for (moves, counts) in PERFT_POSITIONS {
+            let mut game_state = play(moves);
+            for (depth, count) in (1..).zip(counts.iter()) {
+                assert_eq!(game_state.perft(depth), *count, "{moves} at depth {depth}");
+            }
+            assert_eq!(game_state.history().len(), play(moves).history().len());
+        }
+    }
+
+    #[test]
+    fn canonical_boards_ignore_where_the_hive_is() {
+        let board = play("wQ;bQ wQ-;wB1 -wQ;bB1 bQ-;wB1 wQ").board().clone();
+        let offset =
+            HexCoordinate::origin().get_relative(&crate::hex_coordinate::HexDirection::UpLeft);
+
+        let mut moved = Board::default();
+        for (position, stack) in board.stacks() {
+            for tile in stack {
+                moved.push(position.translated(&offset), tile);
+            }
+        }
+        assert_ne!(moved, board);
+        assert_eq!(moved.canonical(), board.canonical());
+        assert_eq!(board.canonical().canonical(), board.canonical());
+    }
+
+    #[test]
+    fn boards_grow_for_hives_wider_than_the_grid() {
+        let ant = tile("wA1");
+        let mut board = Board::default();
+        let mut position = HexCoordinate::origin();
+        let mut line = vec![];
+        for _ in 0..INITIAL_GRID_SIZE * 2 {
+            board.push(position, ant);
+            line.push(position);
+            position = position.get_relative(&HexDirection::Right);
+        }
+        assert!(board.size > INITIAL_GRID_SIZE);
+        assert_eq!(board.positions().copied().collect::<Vec<_>>(), line);
+        assert!(!board.contains(&position));
+
+        board.push(line[0], tile("bB1"));
+        assert_eq!(board.height(&line[0]), 2);
+        assert_eq!(board.pop(&line[0]), Some(tile("bB1")));
+        for position in &line {
+            assert_eq!(board.pop(position), Some(ant));
+        }
+        assert_eq!(board, Board::default());
+    }
+
+    #[test]
+    fn lifted_boards_leave_the_tiles_below() {
+        let game_state = play("wQ;bQ wQ-;wB1 -wQ;bB1 bQ-;wB1 wQ");
+        let board = game_state.board();
+        let beetle = game_state.find_tile(&tile("wB1")).unwrap();
+        let queen = game_state.find_tile(&tile("bQ")).unwrap();
+
+        let lifted = board.lifted(beetle);
+        assert!(lifted.contains(&beetle));
+        assert_eq!(lifted.height(&beetle), 1);
+
+        let lifted = board.lifted(queen);
+        assert!(!lifted.contains(&queen));
+        assert_eq!(
+            lifted.get_surrounding_slidable_tiles(beetle, &[]),
+            board
+                .get_without(&queen)
+                .get_surrounding_slidable_tiles(beetle, &[])
+        );
+    }
+
+    #[test]
+    fn pinned_positions_are_kept_across_moves() {
+        let mut game_state = play("wQ;bQ wQ-;wA1 -wQ;bA1 bQ-");
+        let pinned = game_state.pinned_positions().clone();
+        assert_eq!(pinned, rules::get_pinned_positions(game_state.board()));
+
+        for mv in game_state.legal_moves() {
+            game_state.apply(mv);
+            assert_eq!(
+                game_state.pinned_positions(),
+                &rules::get_pinned_positions(game_state.board())
+            );
+            game_state.undo();
+        }
+        //still there without searching again
+        let last = game_state.history.last().unwrap();
+        assert_eq!(last.pinned.get(), Some(&pinned));
+    }
+}
@@ -1,19 +1,33 @@
 use crate::data::components::Level;
 use bevy::math::{Quat, Vec3};
 use bevy::prelude::{Component, Transform};
+use serde::{Deserialize, Serialize};
 
-#[derive(Component, Default, Copy, Clone, Hash, Debug, Eq, PartialEq)]
+#[derive(
+    Component,
+    Default,
+    Copy,
+    Clone,
+    Hash,
+    Debug,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+)]
 pub struct HexCoordinate {
     x: i32,
     y: i32,
 }
 
 impl HexCoordinate {
-    pub(crate) fn origin() -> HexCoordinate {
+    pub fn origin() -> HexCoordinate {
         HexCoordinate { x: 0, y: 0 }
     }
 
-    pub(crate) fn get_transform(&self, level: &Level, depth_offset: f32) -> Transform {
+    pub fn get_transform(&self, level: &Level, depth_offset: f32) -> Transform {
         let x = self.x as f32 + (self.y as f32 / 2f32);
 
         Transform::from_translation(Vec3 {
@@ -52,8 +66,74 @@ impl HexCoordinate {
             },
         }
     }
+
+    /// Moves the position by the offset of `offset` from the origin.
+    pub fn translated(&self, offset: &HexCoordinate) -> HexCoordinate {
+        HexCoordinate {
+            x: self.x + offset.x,
+            y: self.y + offset.y,
+        }
+    }
+
+    /// The offset that [`HexCoordinate::translated`] needs to move `origin` here.
+    pub fn relative_to(&self, origin: &HexCoordinate) -> HexCoordinate {
+        HexCoordinate {
+            x: self.x - origin.x,
+            y: self.y - origin.y,
+        }
+    }
+
+    /// Turns the position counterclockwise around the origin by 60 degrees `steps` times, so
+    /// e.g. a tile to the right ends up to the up right.
+    pub fn rotated(&self, steps: u8) -> HexCoordinate {
+        (0..steps % 6).fold(*self, |position, _| HexCoordinate {
+            x: -position.y,
+            y: position.x + position.y,
+        })
+    }
+
+    /// Mirrors the position at the row of the origin, which swaps up and down.
+    pub fn reflected(&self) -> HexCoordinate {
+        HexCoordinate {
+            x: self.x + self.y,
+            y: -self.y,
+        }
+    }
+
+    /// The two numbers of the position, `x` growing to the right and `y` to the up right.
+    pub fn axial(&self) -> (i32, i32) {
+        (self.x, self.y)
+    }
+
+    pub fn transformed(&self, symmetry: &Symmetry) -> HexCoordinate {
+        let position = match symmetry.reflected {
+            true => self.reflected(),
+            false => *self,
+        };
+        position.rotated(symmetry.rotation)
+    }
+}
+
+/// One of the twelve ways to map the hex grid onto itself while keeping the origin in place,
+/// by mirroring it or not and then rotating it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Symmetry {
+    pub reflected: bool,
+    /// Counterclockwise steps of 60 degrees.
+    pub rotation: u8,
+}
+
+impl Symmetry {
+    pub fn all() -> impl Iterator<Item = Symmetry> {
+        [false, true].into_iter().flat_map(|reflected| {
+            (0..6).map(move |rotation| Symmetry {
+                reflected,
+                rotation,
+            })
+        })
+    }
 }
-pub const ALL_DIRECTIONS: [&'static HexDirection; 6] = [
+pub const ALL_DIRECTIONS: [&HexDirection; 6] = [
     &HexDirection::UpRight,
     &HexDirection::Right,
     &HexDirection::DownRight,
@@ -73,17 +153,77 @@ pub enum HexDirection {
 }
 
 impl HexDirection {
-    pub(crate) fn get_adjacent_directions(&self) -> [&HexDirection; 2] {
-        let mut index = 0;
-        for i in 0..6 {
-            if ALL_DIRECTIONS[i] == self {
-                index = i;
-            }
-        }
+    pub fn get_adjacent_directions(&self) -> [&HexDirection; 2] {
+        let index = ALL_DIRECTIONS
+            .iter()
+            .position(|direction| *direction == self)
+            .unwrap();
 
-        return [
+        [
             ALL_DIRECTIONS[(index + 5) % 6],
             ALL_DIRECTIONS[(index + 1) % 6],
-        ];
+        ]
+    }
+
+    pub fn get_opposite(&self) -> &HexDirection {
+        let index = ALL_DIRECTIONS
+            .iter()
+            .position(|direction| *direction == self)
+            .unwrap();
+
+        ALL_DIRECTIONS[(index + 3) % 6]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotating_turns_directions() {
+        let origin = HexCoordinate::origin();
+        for (index, direction) in ALL_DIRECTIONS.iter().enumerate() {
+            let position = origin.get_relative(direction);
+            //the directions are listed clockwise
+            let counterclockwise = ALL_DIRECTIONS[(index + 5) % 6];
+            assert_eq!(position.rotated(1), origin.get_relative(counterclockwise));
+            assert_eq!(position.rotated(6), position);
+        }
+    }
+
+    #[test]
+    fn symmetries_keep_neighbours_next_to_each_other() {
+        let position = HexCoordinate::origin()
+            .get_relative(&HexDirection::Right)
+            .get_relative(&HexDirection::Right)
+            .get_relative(&HexDirection::UpRight);
+        assert_eq!(position.reflected().reflected(), position);
+        assert_eq!(
+            HexCoordinate::origin()
+                .get_relative(&HexDirection::UpLeft)
+                .reflected(),
+            HexCoordinate::origin().get_relative(&HexDirection::DownLeft)
+        );
+
+        let mut images = vec![];
+        for symmetry in Symmetry::all() {
+            let image = position.transformed(&symmetry);
+            let neighbours = ALL_DIRECTIONS.map(|direction| {
+                position
+                    .get_relative(direction)
+                    .transformed(&symmetry)
+                    .relative_to(&image)
+            });
+            for direction in ALL_DIRECTIONS {
+                let offset = HexCoordinate::origin().get_relative(direction);
+                assert!(neighbours.contains(&offset), "{symmetry:?}");
+            }
+            images.push(image);
+        }
+
+        //the position is on no axis of symmetry, so it has twelve different images
+        images.sort();
+        images.dedup();
+        assert_eq!(images.len(), 12);
     }
 }
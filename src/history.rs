@@ -0,0 +1,169 @@
+use crate::opponent::PlayerControllers;
+use bevy::app::{App, Plugin, Update};
+use bevy::input::ButtonInput;
+use bevy::prelude::{
+    in_state, on_event, Condition, Event, EventReader, EventWriter, IntoSystemConfigs, KeyCode,
+    NextState, Res, ResMut, Resource,
+};
+use hive_bevy::data::enums::{AppState, Player};
+use hive_bevy::game_state::{GameState, Move, Undone};
+
+/// Moves that were taken back and can be played again. Cleared as soon as a new move is played.
+#[derive(Resource, Default)]
+pub struct RedoStack(pub Vec<Move>);
+
+#[derive(Event, Clone, Copy)]
+pub enum HistoryRequest {
+    Undo,
+    Redo,
+}
+
+fn s_read_history_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut history_requests: EventWriter<HistoryRequest>,
+) {
+    if !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::KeyZ) {
+        history_requests.send(HistoryRequest::Undo);
+    }
+    if keys.just_pressed(KeyCode::KeyY) {
+        history_requests.send(HistoryRequest::Redo);
+    }
+}
+
+/// Whether the game should wait for the player to move in `game_state`, and not for the
+/// computer. Without humans, every move counts.
+fn waits_for_human(game_state: &GameState, controllers: &PlayerControllers) -> bool {
+    controllers.is_human(game_state.current_player())
+        || !(controllers.is_human(Player::Player1) || controllers.is_human(Player::Player2))
+}
+
+/// Takes back moves until a human is to move again, as the computer would otherwise just play
+/// its move again. A forced pass would be played again as well, so the move before is taken
+/// back too. A resignation or an agreed draw is taken back on its own. Returns whether anything
+/// was taken back.
+fn undo_turn(
+    game_state: &mut GameState,
+    redo_stack: &mut RedoStack,
+    controllers: &PlayerControllers,
+) -> bool {
+    let mut changed = false;
+    while let Some(undone) = game_state.undo() {
+        let Undone::Move(mv) = undone else {
+            return true;
+        };
+        redo_stack.0.push(mv);
+        changed = true;
+        if mv != Move::Pass && waits_for_human(game_state, controllers) {
+            break;
+        }
+    }
+    changed
+}
+
+/// Plays taken back moves again until a human is to move.
+fn redo_turn(
+    game_state: &mut GameState,
+    redo_stack: &mut RedoStack,
+    controllers: &PlayerControllers,
+) -> bool {
+    let mut changed = false;
+    while let Some(mv) = redo_stack.0.pop() {
+        game_state.apply(mv);
+        changed = true;
+        if waits_for_human(game_state, controllers) {
+            break;
+        }
+    }
+    changed
+}
+
+fn s_apply_history_requests(
+    mut history_requests: EventReader<HistoryRequest>,
+    controllers: Res<PlayerControllers>,
+    mut game_state: ResMut<GameState>,
+    mut redo_stack: ResMut<RedoStack>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let mut changed = false;
+
+    for request in history_requests.read() {
+        changed |= match request {
+            HistoryRequest::Undo => undo_turn(&mut game_state, &mut redo_stack, &controllers),
+            HistoryRequest::Redo => redo_turn(&mut game_state, &mut redo_stack, &controllers),
+        };
+    }
+
+    if changed {
+        next_state.set(AppState::Rebuilding);
+    }
+}
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RedoStack>();
+        app.add_event::<HistoryRequest>();
+        app.add_systems(
+            Update,
+            (
+                s_read_history_keys,
+                s_apply_history_requests
+                    .after(s_read_history_keys)
+                    .run_if(on_event::<HistoryRequest>())
+                    .run_if(in_state(AppState::Idle).or_else(in_state(AppState::PlayerWon))),
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opponent::Controller;
+    use hive_bevy::ai::SearchSettings;
+    use hive_bevy::bot::Bot;
+    use hive_bevy::notation::play_moves;
+
+    fn against_computer() -> PlayerControllers {
+        PlayerControllers {
+            player1: Controller::Human,
+            player2: Controller::Computer(Bot::AlphaBeta(SearchSettings::default())),
+        }
+    }
+
+    #[test]
+    fn undo_against_the_computer_takes_back_its_reply_too() {
+        let mut game_state = GameState::new();
+        play_moves(&mut game_state, "wQ;bQ wQ-;wA1 -wQ;bA1 bQ-").unwrap();
+        let mut redo_stack = RedoStack::default();
+        let controllers = against_computer();
+
+        assert!(undo_turn(&mut game_state, &mut redo_stack, &controllers));
+        assert_eq!(game_state.history().len(), 2);
+        assert_eq!(game_state.current_player(), Player::Player1);
+
+        assert!(redo_turn(&mut game_state, &mut redo_stack, &controllers));
+        assert_eq!(game_state.history().len(), 4);
+        assert!(redo_stack.0.is_empty());
+    }
+
+    #[test]
+    fn undo_between_humans_takes_back_one_move() {
+        let mut game_state = GameState::new();
+        play_moves(&mut game_state, "wQ;bQ wQ-;wA1 -wQ").unwrap();
+        let mut redo_stack = RedoStack::default();
+        let controllers = PlayerControllers::default();
+
+        assert!(undo_turn(&mut game_state, &mut redo_stack, &controllers));
+        assert_eq!(game_state.history().len(), 2);
+        assert_eq!(game_state.current_player(), Player::Player1);
+
+        while undo_turn(&mut game_state, &mut redo_stack, &controllers) {}
+        assert_eq!(redo_stack.0.len(), 3);
+    }
+}
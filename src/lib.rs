@@ -0,0 +1,12 @@
+pub mod ai;
+pub mod bot;
+pub mod data;
+pub mod evaluation;
+pub mod game_state;
+pub mod hex_coordinate;
+pub mod mcts;
+pub mod notation;
+pub mod rule_set;
+pub mod rules;
+pub mod save_game;
+pub mod zobrist;
@@ -1,49 +1,96 @@
-//! Renders an animated sprite by loading all animation frames from a single image (a sprite sheet)
-//! into a texture atlas, and changing the displayed image periodically.
-
-use crate::data::components::{
-    ColorMaterials, CurrentPlayer, GameAssets, GameResultResource, HasTileOnTop, HiveTile,
-    IsInGame, IsOnTopOf, Level, MainCamera, PlacableTileState, PlayerInventory, PositionCache,
-    PositionCacheEntry, PossiblePlacementMarker, PossiblePlacementTag, SelectedTile, Sprites,
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use crate::game_end::GameEndPlugin;
+use crate::history::{HistoryPlugin, RedoStack};
+use crate::new_game::NewGamePlugin;
+use crate::opponent::{OpponentPlugin, PlayerControllers};
+use crate::persistence::PersistencePlugin;
+use crate::replay::ReplayPlugin;
+use crate::ui::{
+    s_handle_controller_buttons, s_handle_game_end_buttons, s_handle_history_buttons,
+    s_handle_new_game_buttons, s_handle_replay_buttons, s_handle_replay_timeline,
+    s_handle_save_game_buttons, s_setup_ui, s_update_controller_buttons, s_update_game_end_buttons,
+    s_update_new_game_buttons, s_update_replay_bar, s_update_ui_for_round,
 };
-pub use crate::data::enums::InsectType::*;
-use crate::data::enums::Player::{Player1, Player2};
-use crate::data::enums::{AppState, GameResult, InsectType, Player};
-use crate::hex_coordinate::ALL_DIRECTIONS;
-use crate::ui::{s_setup_ui, s_update_ui_for_round};
 use crate::world_cursor::{PressState, WorldCursor, WorldCursorPlugin};
 use bevy::math::vec3;
 use bevy::prelude::*;
 use bevy::render::camera::ScalingMode;
 use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
-use hex_coordinate::HexCoordinate;
-
-mod data;
-mod hex_coordinate;
-mod rules;
+use hive_bevy::data::components::{
+    ColorMaterials, CurrentPlayer, GameAssets, GameResultResource, HasTileOnTop, HiveTile,
+    IsInGame, IsOnTopOf, Level, MainCamera, PlacableTileState, PlayerInventory, PositionCache,
+    PositionCacheEntry, PossiblePlacementMarker, PossiblePlacementTag, SelectedTile, Sprites,
+};
+use hive_bevy::data::enums::Player::{Player1, Player2};
+use hive_bevy::data::enums::{AppState, InsectType, Player};
+use hive_bevy::game_state::{GameState, Move};
+use hive_bevy::hex_coordinate::HexCoordinate;
+use hive_bevy::notation::format_move;
+
+mod game_end;
+mod history;
+mod new_game;
+mod opponent;
+mod persistence;
+mod replay;
 mod ui;
 mod world_cursor;
 
 fn main() {
     App::new()
-        .add_plugins((DefaultPlugins, WorldCursorPlugin))
+        .add_plugins((
+            DefaultPlugins,
+            WorldCursorPlugin,
+            HistoryPlugin,
+            PersistencePlugin,
+            ReplayPlugin,
+            OpponentPlugin,
+            NewGamePlugin,
+            GameEndPlugin,
+        ))
         .init_state::<AppState>()
         .add_systems(Startup, (setup_assets, setup.after(setup_assets)))
         .add_systems(Startup, s_setup_ui)
         .add_systems(OnEnter(AppState::Init), s_init)
+        .add_systems(
+            OnEnter(AppState::Rebuilding),
+            (s_rebuild_board, s_finish_rebuilding).chain(),
+        )
+        .add_systems(
+            Update,
+            s_rebuild_board
+                .run_if(in_state(AppState::Replay).and_then(resource_changed::<GameState>)),
+        )
         .add_systems(Update, (s_build_cache, s_update_camera))
-        .add_systems(OnEnter(AppState::Idle), s_spawn_tiles_from_inventory)
-        .add_systems(Update, s_update_ui_for_round)
+        .add_systems(
+            OnEnter(AppState::Idle),
+            (s_spawn_tiles_from_inventory, s_pass_without_moves),
+        )
+        .add_systems(
+            Update,
+            (
+                s_update_ui_for_round,
+                s_handle_history_buttons,
+                s_handle_save_game_buttons,
+                s_handle_replay_buttons,
+                s_handle_replay_timeline,
+                s_update_replay_bar,
+                s_handle_controller_buttons,
+                s_update_controller_buttons,
+                s_handle_new_game_buttons,
+                s_update_new_game_buttons,
+                s_handle_game_end_buttons,
+                s_update_game_end_buttons,
+            ),
+        )
         .add_systems(
             Update,
             s_update_idle
                 .after(s_build_cache)
                 .run_if(in_state(AppState::Idle)),
         )
-        .add_systems(
-            OnEnter(AppState::MovingTile),
-            rules::s_spawn_placement_markers,
-        )
+        .add_systems(OnEnter(AppState::MovingTile), s_spawn_placement_markers)
         .add_systems(
             Update,
             s_move_tile
@@ -62,7 +109,60 @@ fn main() {
 }
 
 fn s_init(mut next_state: ResMut<NextState<AppState>>) {
-    next_state.set(AppState::Idle);
+    next_state.set(AppState::Rebuilding);
+}
+
+/// Respawns all tiles and syncs the ECS resources to match the [`GameState`].
+fn s_rebuild_board(
+    game_state: Res<GameState>,
+    game_assets: Res<GameAssets>,
+    q_tiles: Query<Entity, Or<(With<InsectType>, With<PossiblePlacementTag>)>>,
+    mut q_inventory: Query<(&mut PlayerInventory, &Player)>,
+    mut current_player: ResMut<CurrentPlayer>,
+    mut game_result: ResMut<GameResultResource>,
+    mut commands: Commands,
+) {
+    for entity in &q_tiles {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    for (hex, stack) in game_state.board().stacks() {
+        let mut tile_below = None;
+        for (level, tile) in stack.enumerate() {
+            let level = Level(level as u32);
+            let entity = spawn_tile(
+                &mut commands,
+                &game_assets,
+                tile.player,
+                tile.insect,
+                level,
+                hex.get_transform(&level, 0.),
+            );
+            commands.entity(entity).insert((IsInGame {}, *hex));
+
+            if let Some(tile_below) = tile_below {
+                commands.entity(tile_below).insert(HasTileOnTop {});
+                commands.entity(entity).insert(IsOnTopOf { tile_below });
+            }
+            tile_below = Some(entity);
+        }
+    }
+
+    for (mut inventory, player) in &mut q_inventory {
+        *inventory = game_state.inventory(*player).clone();
+    }
+    current_player.player = game_state.current_player();
+    game_result.result = game_state.result();
+}
+
+fn s_finish_rebuilding(
+    game_result: Res<GameResultResource>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    match game_result.result {
+        None => next_state.set(AppState::Idle),
+        Some(_) => next_state.set(AppState::PlayerWon),
+    }
 }
 
 fn setup_assets(
@@ -71,15 +171,9 @@ fn setup_assets(
     mut materials: ResMut<Assets<ColorMaterial>>,
     asset_server: Res<AssetServer>,
 ) {
-    let red_material = materials.add(Color::LinearRgba(
-        LinearRgba::new(1.0, 0.0, 0.0, 1.0).into(),
-    ));
-    let white_material = materials.add(Color::LinearRgba(
-        LinearRgba::new(1.0, 1.0, 1.0, 1.0).into(),
-    ));
-    let grey_material = materials.add(Color::LinearRgba(
-        LinearRgba::new(0.2, 0.2, 0.2, 1.0).into(),
-    ));
+    let red_material = materials.add(Color::LinearRgba(LinearRgba::new(1.0, 0.0, 0.0, 1.0)));
+    let white_material = materials.add(Color::LinearRgba(LinearRgba::new(1.0, 1.0, 1.0, 1.0)));
+    let grey_material = materials.add(Color::LinearRgba(LinearRgba::new(0.2, 0.2, 0.2, 1.0)));
 
     let color_materials = ColorMaterials {
         red: red_material,
@@ -94,6 +188,9 @@ fn setup_assets(
         spider: asset_server.load("spider.png"),
         grasshopper: asset_server.load("grasshopper.png"),
         beetle: asset_server.load("beetle.png"),
+        pillbug: asset_server.load("pillbug.png"),
+        mosquito: asset_server.load("mosquito.png"),
+        ladybug: asset_server.load("ladybug.png"),
     };
 
     commands.insert_resource(GameAssets {
@@ -103,24 +200,11 @@ fn setup_assets(
     });
 }
 
-fn setup(mut commands: Commands, game_assets: Res<GameAssets>) {
+fn setup(mut commands: Commands) {
     commands.spawn((Camera2dBundle::default(), MainCamera, IsDefaultUiCamera));
 
-    let origin = HexCoordinate::origin();
-    let bundle = PossiblePlacementMarker {
-        renderer: MaterialMesh2dBundle {
-            mesh: game_assets.mesh.clone(),
-            material: game_assets.color_materials.grey.clone(),
-            transform: origin.get_transform(&Level(0), -2.),
-            ..default()
-        },
-        possible_placement_tag: Default::default(),
-        hex_coordinate: origin,
-    };
-    commands.spawn(bundle);
-
-    commands.spawn((PlayerInventory::new(), Player1));
-    commands.spawn((PlayerInventory::new(), Player2));
+    commands.spawn((PlayerInventory::default(), Player1));
+    commands.spawn((PlayerInventory::default(), Player2));
 }
 fn s_update_camera(
     res_position_cache: Res<PositionCache>,
@@ -187,8 +271,8 @@ fn s_build_cache(
         position_cache.0.insert(
             *hex,
             PositionCacheEntry {
-                player: player.clone(),
-                _insect_type: insect_type.clone(),
+                player: *player,
+                _insect_type: *insect_type,
                 entity,
             },
         );
@@ -198,14 +282,11 @@ fn s_build_cache(
 fn s_cleanup_tile_placement(
     q_possible_placements: Query<Entity, With<PossiblePlacementTag>>,
     q_placable_tiles: Query<Entity, With<PlacableTileState>>,
-    q_in_game_tiles: Query<&IsInGame>,
     mut q_transforms_with_hex_coord: Query<(&mut Transform, &HexCoordinate, &Level)>,
     mut commands: Commands,
 ) {
-    if !q_in_game_tiles.is_empty() {
-        for entity in &q_possible_placements {
-            commands.entity(entity).despawn_recursive();
-        }
+    for entity in &q_possible_placements {
+        commands.entity(entity).despawn_recursive();
     }
     for entity in &q_placable_tiles {
         commands.entity(entity).despawn_recursive();
@@ -219,120 +300,147 @@ fn s_cleanup_tile_placement(
 fn s_enter_move_finished(
     mut next_state: ResMut<NextState<AppState>>,
     mut current_player: ResMut<CurrentPlayer>,
-    q_bee: Query<(&InsectType, &Player, &HexCoordinate)>,
-    position_cache: Res<PositionCache>,
+    game_state: Res<GameState>,
     mut commands: Commands,
 ) {
-    let mut players_that_lost = vec![];
-
-    for (insect_type, queen_player, hex) in &q_bee {
-        if insect_type != &Queen {
-            continue;
-        }
-
-        if queen_player == &current_player.player {
-            continue;
-        }
-
-        let surrounded = ALL_DIRECTIONS
-            .iter()
-            .map(|direction| hex.get_relative(direction))
-            .all(|relative_position| position_cache.0.contains_key(&relative_position));
-
-        if surrounded {
-            players_that_lost.push(queen_player);
-        }
-    }
-
-    match players_that_lost.len() {
-        0 => {
-            match current_player.player {
-                Player1 => current_player.player = Player2,
-                Player2 => current_player.player = Player1,
-            }
+    match game_state.result() {
+        None => {
+            current_player.player = game_state.current_player();
             next_state.set(AppState::Idle);
         }
-        1 => {
-            let player_that_won = match players_that_lost[0] {
-                Player1 => Player2,
-                Player2 => Player1,
-            };
-            commands.insert_resource(GameResultResource {
-                result: Some(GameResult::PlayerWon(player_that_won)),
-            });
-            next_state.set(AppState::PlayerWon);
-        }
-        _ => {
+        Some(result) => {
             commands.insert_resource(GameResultResource {
-                result: Some(GameResult::Draw),
+                result: Some(result),
             });
             next_state.set(AppState::PlayerWon);
         }
     }
 }
 
-fn s_spawn_tiles_from_inventory(
-    q_inventory: Query<(&PlayerInventory, &Player)>,
+fn s_spawn_placement_markers(
+    game_state: Res<GameState>,
+    q_insect: Query<&InsectType>,
+    q_hex_coord: Query<&HexCoordinate, With<IsInGame>>,
     game_assets: Res<GameAssets>,
-    current_player: Res<CurrentPlayer>,
+    selected_tile: Res<SelectedTile>,
     mut commands: Commands,
 ) {
-    let current_player = &current_player.player.clone();
-    let mut inventory = None;
-    for (i, player) in &q_inventory {
-        if player == current_player {
-            inventory = Some(i);
-        }
-    }
-
-    let inventory = inventory.unwrap();
-
-    let mut offset = -400.0;
-
-    //the queen needs to be played within the first 3 moves
-    let pieces_to_spawn = match inventory.moves_played == 2 && inventory.pieces.contains(&Queen) {
-        true => {
-            vec![Queen]
+    let valid_moves = match q_hex_coord.get(selected_tile.0) {
+        Ok(position) => game_state.moves_from(*position),
+        Err(_) => {
+            let insect = q_insect.get(selected_tile.0).unwrap();
+            match game_state.pieces_to_place().contains(insect) {
+                true => game_state.placements(),
+                false => vec![],
+            }
         }
-        false => inventory.pieces.clone(),
     };
 
-    for insect in pieces_to_spawn {
-        let material = match current_player {
-            Player1 => game_assets.color_materials.white.clone(),
-            Player2 => game_assets.color_materials.red.clone(),
-        };
-        let position = Transform::from_translation(Vec3::new(offset, -300., 0.));
-
-        let bundle = HiveTile {
+    for valid_move in valid_moves {
+        let bundle = PossiblePlacementMarker {
             renderer: MaterialMesh2dBundle {
                 mesh: game_assets.mesh.clone(),
-                material,
-                transform: position,
+                material: game_assets.color_materials.grey.clone(),
+                transform: valid_move
+                    .get_transform(&Level(0), -2.)
+                    .with_scale(Vec3::new(1.2, 1.2, 1.2)),
                 ..default()
             },
-            player: current_player.clone(),
-            placable_tile_tag: PlacableTileState {},
-            insect,
-            level: Level(0),
+            possible_placement_tag: Default::default(),
+            hex_coordinate: valid_move,
         };
+        commands.spawn(bundle);
+    }
+}
 
-        let child = commands
-            .spawn(SpriteBundle {
-                texture: game_assets.sprites.get(insect),
-                transform: Transform::from_scale(vec3(0.15, 0.15, 0.15))
-                    .with_translation(Vec3::new(0.0f32, 0.0f32, 10.0f32)),
-                ..default()
-            })
-            .id();
+/// Passes for a human player that has nothing else to do, computer players pass on their own.
+fn s_pass_without_moves(
+    mut game_state: ResMut<GameState>,
+    current_player: Res<CurrentPlayer>,
+    controllers: Res<PlayerControllers>,
+    mut redo_stack: ResMut<RedoStack>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if !controllers.is_human(current_player.player) || !game_state.must_pass() {
+        return;
+    }
+
+    info!("{:?} has no legal move and passes", current_player.player);
+    game_state.apply(Move::Pass);
+    //redoing the move before a forced pass leads to the same pass again
+    if redo_stack.0.last() == Some(&Move::Pass) {
+        redo_stack.0.pop();
+    } else {
+        redo_stack.0.clear();
+    }
+    next_state.set(AppState::Rebuilding);
+}
+
+fn s_spawn_tiles_from_inventory(
+    game_state: Res<GameState>,
+    game_assets: Res<GameAssets>,
+    current_player: Res<CurrentPlayer>,
+    mut commands: Commands,
+) {
+    let current_player = &current_player.player;
+
+    let mut offset = -400.0;
 
-        let parent = commands.spawn(bundle).id();
-        commands.entity(parent).push_children(&[child]);
+    for insect in game_state.pieces_to_place() {
+        let position = Transform::from_translation(Vec3::new(offset, -300., 0.));
+        let entity = spawn_tile(
+            &mut commands,
+            &game_assets,
+            *current_player,
+            insect,
+            Level(0),
+            position,
+        );
+        commands.entity(entity).insert(PlacableTileState {});
 
         offset += 100.;
     }
 }
 
+fn spawn_tile(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    player: Player,
+    insect: InsectType,
+    level: Level,
+    transform: Transform,
+) -> Entity {
+    let material = match player {
+        Player1 => game_assets.color_materials.white.clone(),
+        Player2 => game_assets.color_materials.red.clone(),
+    };
+
+    let bundle = HiveTile {
+        renderer: MaterialMesh2dBundle {
+            mesh: game_assets.mesh.clone(),
+            material,
+            transform,
+            ..default()
+        },
+        player,
+        insect,
+        level,
+    };
+
+    let child = commands
+        .spawn(SpriteBundle {
+            texture: game_assets.sprites.get(insect),
+            transform: Transform::from_scale(vec3(0.15, 0.15, 0.15))
+                .with_translation(Vec3::new(0.0f32, 0.0f32, 10.0f32)),
+            ..default()
+        })
+        .id();
+
+    let parent = commands.spawn(bundle).id();
+    commands.entity(parent).push_children(&[child]);
+    parent
+}
+
 fn s_update_idle(
     world_cursor: Res<WorldCursor>,
     mut q_placable_tiles: Query<
@@ -346,13 +454,26 @@ fn s_update_idle(
     mut commands: Commands,
     q_camera: Query<(&OrthographicProjection, &Transform), With<Camera2d>>,
     q_is_in_game: Query<&IsInGame>,
+    q_hex_coord: Query<&HexCoordinate, With<IsInGame>>,
+    game_state: Res<GameState>,
     current_player: Res<CurrentPlayer>,
+    controllers: Res<PlayerControllers>,
     mut next_state: ResMut<NextState<AppState>>,
 ) {
+    if !controllers.is_human(current_player.player) {
+        return;
+    }
+
     match world_cursor.press_state {
         PressState::JustPressed => {
             for (entity, transform, player) in &mut q_placable_tiles {
-                if *player != current_player.player {
+                //tiles in the hive can only be picked up if they can go somewhere, which also
+                //covers tiles of the opponent that can be moved by a pillbug
+                let may_be_moved = match q_hex_coord.get(entity) {
+                    Ok(hex) => !game_state.moves_from(*hex).is_empty(),
+                    Err(_) => *player == current_player.player,
+                };
+                if !may_be_moved {
                     continue;
                 }
 
@@ -362,7 +483,7 @@ fn s_update_idle(
                     .distance(Vec2::new(transform.translation.x, transform.translation.y));
 
                 if distance_to_cursor < max_distance {
-                    commands.insert_resource(SelectedTile(entity.clone()));
+                    commands.insert_resource(SelectedTile(entity));
 
                     next_state.set(AppState::MovingTile);
                     break;
@@ -378,11 +499,8 @@ fn s_update_idle(
             let lower_border_y = camera_transform.translation.y - vertical_half_size;
 
             for (entity, mut transform, _) in &mut q_placable_tiles {
-                match &q_is_in_game.get(entity) {
-                    Ok(_) => {}
-                    Err(_) => {
-                        transform.translation.y = lower_border_y + 60.;
-                    }
+                if q_is_in_game.get(entity).is_err() {
+                    transform.translation.y = lower_border_y + 60.;
                 }
             }
         }
@@ -393,7 +511,6 @@ fn s_update_idle(
 
 fn s_move_tile(
     world_cursor: Res<WorldCursor>,
-    // mut q_transform:  Query<(&mut Transform)>,
     mut q_possible_placements: Query<&mut Transform, Without<PossiblePlacementTag>>,
     mut m_placement_markers: Query<(&Transform, &HexCoordinate), With<PossiblePlacementTag>>,
     q_placable_tile_state: Query<&PlacableTileState>,
@@ -401,15 +518,18 @@ fn s_move_tile(
     q_level: Query<(&Level,)>,
     q_is_on_top_of: Query<(&mut IsOnTopOf,)>,
     q_insect: Query<&InsectType>,
+    q_hex_coord: Query<&HexCoordinate, Without<PossiblePlacementTag>>,
     mut commands: Commands,
     selected_tile: Res<SelectedTile>,
     mut next_state: ResMut<NextState<AppState>>,
     current_player: Res<CurrentPlayer>,
     position_cache: Res<PositionCache>,
+    mut game_state: ResMut<GameState>,
+    mut redo_stack: ResMut<RedoStack>,
 ) {
     let selected_entity = selected_tile.0;
 
-    let current_player = &current_player.player.clone();
+    let current_player = &current_player.player;
     let mut inventory = None;
     for (i, player) in &mut q_inventory {
         if player == current_player {
@@ -419,11 +539,7 @@ fn s_move_tile(
 
     let mut inventory = inventory.unwrap();
 
-    //    let mut current_position = q_hex_coord_of_existing.get(selected_tile.0) ;
-
     match world_cursor.press_state {
-        // PressState::Released => {}
-        // PressState::JustPressed => {}
         PressState::Pressed => {
             if let Ok(mut transform) = q_possible_placements.get_mut(selected_entity) {
                 transform.translation =
@@ -431,7 +547,6 @@ fn s_move_tile(
             }
         }
 
-        //PressState::JustReleased => {}
         _ => {
             if let Ok(selected_transform) = q_possible_placements.get_mut(selected_entity) {
                 for (possible_placement, possible_hex_coordinate) in &mut m_placement_markers {
@@ -441,47 +556,48 @@ fn s_move_tile(
                         .distance(selected_transform.translation.with_z(0.))
                         < 50.
                     {
-                        // commands.spawn(HiveTile::new(*hex_coordinate, &game_assets, current_player.player));
-
-                        match q_placable_tile_state.get(selected_entity) {
+                        let mv = match q_placable_tile_state.get(selected_entity) {
                             Ok(_) => {
-                                let mut new_pieces = inventory.pieces.clone();
-
-                                new_pieces.remove(
-                                    new_pieces
-                                        .iter()
-                                        .position(|i| i == q_insect.get(selected_entity).unwrap())
-                                        .unwrap(),
-                                );
-
-                                inventory.pieces = new_pieces;
-
                                 commands
                                     .entity(selected_entity)
                                     .insert(IsInGame {})
-                                    .insert(possible_hex_coordinate.clone())
+                                    .insert(*possible_hex_coordinate)
                                     .remove::<PlacableTileState>();
+
+                                Move::Place {
+                                    insect: *q_insect.get(selected_entity).unwrap(),
+                                    at: *possible_hex_coordinate,
+                                }
                             }
                             Err(_) => {
-                                match q_is_on_top_of.get(selected_entity) {
-                                    Ok(is_on_top_of) => {
-                                        commands
-                                            .entity(is_on_top_of.0.tile_below)
-                                            .remove::<HasTileOnTop>();
-                                    }
-                                    Err(_) => {}
+                                if let Ok(is_on_top_of) = q_is_on_top_of.get(selected_entity) {
+                                    commands
+                                        .entity(is_on_top_of.0.tile_below)
+                                        .remove::<HasTileOnTop>();
                                 }
 
                                 commands
                                     .entity(selected_entity)
-                                    .insert(possible_hex_coordinate.clone());
+                                    .insert(*possible_hex_coordinate);
+
+                                Move::Move {
+                                    from: *q_hex_coord.get(selected_entity).unwrap(),
+                                    to: *possible_hex_coordinate,
+                                }
                             }
-                        }
+                        };
 
-                        inventory.moves_played += 1;
+                        info!(
+                            "{:?} played {}",
+                            current_player,
+                            format_move(&game_state, &mv)
+                        );
+                        game_state.apply(mv);
+                        redo_stack.0.clear();
+                        *inventory = game_state.inventory(*current_player).clone();
                         next_state.set(AppState::MoveFinished);
 
-                        match position_cache.0.get(&possible_hex_coordinate) {
+                        match position_cache.0.get(possible_hex_coordinate) {
                             None => {
                                 commands
                                     .entity(selected_entity)
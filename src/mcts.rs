@@ -0,0 +1,380 @@
+//! A computer opponent using Monte Carlo tree search with the UCT selection rule.
+
+use crate::data::enums::{GameResult, InsectType, Player};
+use crate::evaluation::queen_pressure;
+use crate::game_state::{GameState, Move};
+use crate::hex_coordinate::ALL_DIRECTIONS;
+use bevy::utils::{Duration, Instant};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+/// How a simulated game is played out once it leaves the search tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Playout {
+    /// Random moves until the game ends or `playout_depth` is reached, which counts as a draw.
+    Random,
+    /// Prefers moves next to the queen of the opponent and rates unfinished playouts by how
+    /// much more the queen of the opponent is surrounded.
+    Heuristic,
+}
+
+/// The search stops after `iterations` simulated games or when `time_budget` runs out,
+/// whichever comes first. Searches with the same `seed` and position play the same move as
+/// long as they are not cut short by the time budget.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MctsSettings {
+    pub iterations: u32,
+    pub time_budget: Duration,
+    pub exploration: f32,
+    pub playout: Playout,
+    pub playout_depth: u32,
+    pub seed: u64,
+}
+
+impl Default for MctsSettings {
+    fn default() -> Self {
+        MctsSettings {
+            iterations: 2000,
+            time_budget: Duration::from_secs(2),
+            exploration: std::f32::consts::SQRT_2,
+            playout: Playout::Heuristic,
+            playout_depth: 40,
+            seed: 0,
+        }
+    }
+}
+
+struct Node {
+    mv: Move,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried_moves: Vec<Move>,
+    /// The player that played `mv`, who the wins are counted for.
+    player: Player,
+    visits: u32,
+    wins: f32,
+    /// Whether `mv` is known to win (`Some(true)`) or to lose (`Some(false)`) for `player`, because
+    /// it ends the game or because of the proven moves below it.
+    proven: Option<bool>,
+}
+
+impl Node {
+    fn new(game_state: &GameState, mv: Move, parent: Option<usize>) -> Node {
+        Node {
+            mv,
+            parent,
+            children: vec![],
+            untried_moves: moves_to_try(game_state),
+            player: game_state.current_player().other(),
+            visits: 0,
+            wins: 0.,
+            proven: game_state
+                .result()
+                .and_then(|result| result.winner())
+                .map(|winner| winner == game_state.current_player().other()),
+        }
+    }
+
+    /// Proven wins are always chosen and proven losses only if nothing else is left.
+    fn uct(&self, parent_visits: u32, exploration: f32) -> f32 {
+        match self.proven {
+            Some(true) => f32::INFINITY,
+            Some(false) => f32::NEG_INFINITY,
+            None => {
+                let visits = self.visits as f32;
+                self.wins / visits + exploration * ((parent_visits as f32).ln() / visits).sqrt()
+            }
+        }
+    }
+}
+
+/// The legal moves, or a pass if there are none but the game goes on.
+fn moves_to_try(game_state: &GameState) -> Vec<Move> {
+    if game_state.result().is_some() {
+        return vec![];
+    }
+
+    match game_state.legal_moves() {
+        moves if moves.is_empty() => vec![Move::Pass],
+        moves => moves,
+    }
+}
+
+/// Returns a move proven to win or else the most visited move for the current player of
+/// `game_state`, or [`Move::Pass`] if there is nothing else to play.
+pub fn best_move(game_state: &GameState, settings: &MctsSettings) -> Move {
+    //every position gets its own sequence of random numbers
+    let mut rng = SmallRng::seed_from_u64(settings.seed ^ game_state.history().len() as u64);
+    let mut game_state = game_state.clone();
+
+    let nodes = vec![Node::new(&game_state, Move::Pass, None)];
+    match nodes[0].untried_moves.as_slice() {
+        [] => return Move::Pass,
+        [mv] => return *mv,
+        _ => {}
+    }
+
+    //random playouts easily overlook a win, so decisive moves are played right away
+    let player = game_state.current_player();
+    for mv in nodes[0].untried_moves.clone() {
+        game_state.apply(mv);
+        let result = game_state.result();
+        game_state.undo();
+        if result.and_then(|result| result.winner()) == Some(player) {
+            return mv;
+        }
+    }
+
+    search(&mut game_state, nodes, settings, &mut rng)
+}
+
+/// Grows the tree below the root in `nodes` and returns the move to play, see [`best_move`].
+fn search(
+    game_state: &mut GameState,
+    mut nodes: Vec<Node>,
+    settings: &MctsSettings,
+    rng: &mut SmallRng,
+) -> Move {
+    let deadline = Instant::now().checked_add(settings.time_budget);
+
+    //the root needs at least one child to choose from
+    for iteration in 0..settings.iterations.max(1) {
+        if iteration > 0 && deadline.is_some_and(|deadline| Instant::now() > deadline) {
+            break;
+        }
+
+        let mut node = 0;
+        let mut applied = 0;
+
+        //selection
+        while nodes[node].untried_moves.is_empty() && !nodes[node].children.is_empty() {
+            let parent_visits = nodes[node].visits;
+            node = *nodes[node]
+                .children
+                .iter()
+                .max_by(|a, b| {
+                    let a = nodes[**a].uct(parent_visits, settings.exploration);
+                    let b = nodes[**b].uct(parent_visits, settings.exploration);
+                    a.total_cmp(&b)
+                })
+                .unwrap();
+            game_state.apply(nodes[node].mv);
+            applied += 1;
+        }
+
+        //expansion
+        if !nodes[node].untried_moves.is_empty() {
+            let index = rng.gen_range(0..nodes[node].untried_moves.len());
+            let mv = nodes[node].untried_moves.swap_remove(index);
+            game_state.apply(mv);
+            applied += 1;
+
+            let child = nodes.len();
+            nodes.push(Node::new(game_state, mv, Some(node)));
+            nodes[node].children.push(child);
+            node = child;
+        }
+
+        //simulation
+        let (winner, applied_in_playout) = playout(game_state, settings, rng);
+        for _ in 0..applied + applied_in_playout {
+            game_state.undo();
+        }
+
+        //backpropagation
+        let mut current = Some(node);
+        while let Some(index) = current {
+            let node = &mut nodes[index];
+            node.visits += 1;
+            node.wins += match winner {
+                Some((player, reward)) if player == node.player => reward,
+                Some((_, reward)) => 1. - reward,
+                None => 0.5,
+            };
+            current = node.parent;
+            prove(&mut nodes, index);
+        }
+    }
+
+    nodes[0]
+        .children
+        .iter()
+        .map(|child| &nodes[*child])
+        .max_by_key(|child| {
+            let proof = match child.proven {
+                Some(true) => 2,
+                None => 1,
+                Some(false) => 0,
+            };
+            (proof, child.visits)
+        })
+        .map(|child| child.mv)
+        .unwrap()
+}
+
+/// Proves the move of the node at `index` from its children: it loses if the opponent has a
+/// winning answer, and it wins if every answer of the opponent is tried and loses.
+fn prove(nodes: &mut [Node], index: usize) {
+    let node = &nodes[index];
+    if node.proven.is_some() || node.children.is_empty() {
+        return;
+    }
+
+    let mut answers = node.children.iter().map(|child| nodes[*child].proven);
+    if answers.clone().any(|proven| proven == Some(true)) {
+        nodes[index].proven = Some(false);
+    } else if node.untried_moves.is_empty() && answers.all(|proven| proven == Some(false)) {
+        nodes[index].proven = Some(true);
+    }
+}
+
+/// Plays the game out and returns the player that is ahead with a reward between 0.5 and 1,
+/// or `None` for a draw, together with the number of moves played.
+fn playout(
+    game_state: &mut GameState,
+    settings: &MctsSettings,
+    rng: &mut SmallRng,
+) -> (Option<(Player, f32)>, u32) {
+    let mut applied = 0;
+
+    while applied < settings.playout_depth {
+        match game_state.result() {
+            Some(GameResult::PlayerWon(player, _)) => return (Some((player, 1.)), applied),
+            Some(GameResult::Draw(_)) => return (None, applied),
+            None => {}
+        }
+
+        let moves = moves_to_try(game_state);
+        let mv = match settings.playout {
+            Playout::Random => moves[rng.gen_range(0..moves.len())],
+            Playout::Heuristic => {
+                let attacking = attacking_moves(game_state, &moves);
+                match attacking.is_empty() || rng.gen_bool(0.5) {
+                    true => moves[rng.gen_range(0..moves.len())],
+                    false => attacking[rng.gen_range(0..attacking.len())],
+                }
+            }
+        };
+        game_state.apply(mv);
+        applied += 1;
+    }
+
+    let winner = match game_state.result() {
+        Some(GameResult::PlayerWon(player, _)) => Some((player, 1.)),
+        Some(GameResult::Draw(_)) => None,
+        None if settings.playout == Playout::Random => None,
+        None => {
+            let player = game_state.current_player();
+            match queen_pressure(game_state, player) {
+                0 => None,
+                pressure if pressure > 0 => Some((player, 0.5 + 0.05 * pressure.min(6) as f32)),
+                pressure => Some((player.other(), 0.5 + 0.05 * (-pressure).min(6) as f32)),
+            }
+        }
+    };
+
+    (winner, applied)
+}
+
+/// The moves ending next to the queen of the opponent.
+fn attacking_moves(game_state: &GameState, moves: &[Move]) -> Vec<Move> {
+    let player = game_state.current_player();
+    let Some(queen) = game_state
+        .board()
+        .stacks()
+        .find_map(|(position, mut stack)| {
+            stack
+                .any(|tile| tile.insect == InsectType::Queen && tile.player != player)
+                .then_some(*position)
+        })
+    else {
+        return vec![];
+    };
+
+    let around_queen = ALL_DIRECTIONS.map(|direction| queen.get_relative(direction));
+    moves
+        .iter()
+        .filter(|mv| match mv {
+            Move::Place { at, .. } => around_queen.contains(at),
+            Move::Move { from, to } => around_queen.contains(to) && !around_queen.contains(from),
+            Move::Pass => false,
+        })
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notation::{format_move, play, WINNING_POSITION};
+
+    fn settings(seed: u64) -> MctsSettings {
+        MctsSettings {
+            iterations: 100,
+            time_budget: Duration::MAX,
+            playout_depth: 10,
+            seed,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn finds_the_winning_move() {
+        let game_state = play(WINNING_POSITION);
+
+        let mv = best_move(&game_state, &settings(1));
+        let mut after = game_state.clone();
+        after.try_apply(mv).unwrap();
+        assert_eq!(
+            after.result().and_then(|result| result.winner()),
+            Some(game_state.current_player()),
+            "{}",
+            format_move(&game_state, &mv)
+        );
+    }
+
+    #[test]
+    fn search_finds_the_winning_move_by_itself() {
+        let game_state = play(WINNING_POSITION);
+
+        //skips the shortcut for decisive moves in best_move, so the search has to try each of the
+        //83 moves. White wins most playouts after any of them, so only the proof tells them apart.
+        for seed in 0..3 {
+            let mut searched = game_state.clone();
+            let root = Node::new(&searched, Move::Pass, None);
+            let mut rng = SmallRng::seed_from_u64(seed);
+            let mv = search(&mut searched, vec![root], &settings(seed), &mut rng);
+            assert_eq!(searched.zobrist_hash(), game_state.zobrist_hash());
+
+            let mut after = game_state.clone();
+            after.try_apply(mv).unwrap();
+            assert_eq!(
+                after.result().and_then(|result| result.winner()),
+                Some(game_state.current_player()),
+                "{}",
+                format_move(&game_state, &mv)
+            );
+        }
+    }
+
+    #[test]
+    fn plays_a_legal_move_without_iterations() {
+        let game_state = play("wS1;bS1 -wS1;wQ wS1-;bQ -bS1");
+        let settings = MctsSettings {
+            iterations: 0,
+            ..settings(0)
+        };
+        assert!(game_state.is_legal(&best_move(&game_state, &settings)));
+    }
+
+    #[test]
+    fn same_seed_plays_the_same_move() {
+        let game_state = play("wS1;bS1 -wS1;wQ wS1-;bQ -bS1");
+
+        //the moves are pinned, so a search in another run of the program has to agree
+        for (seed, expected) in [(0, "wG1 /wQ"), (1, "wA1 wQ\\"), (2, "wB1 wQ/")] {
+            let mv = best_move(&game_state, &settings(seed));
+            assert_eq!(format_move(&game_state, &mv), expected, "seed {seed}");
+        }
+    }
+}
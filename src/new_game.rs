@@ -0,0 +1,105 @@
+use crate::game_end::DrawOffer;
+use crate::history::RedoStack;
+use bevy::app::{App, Plugin, Update};
+use bevy::input::ButtonInput;
+use bevy::log::{error, info};
+use bevy::prelude::{
+    in_state, on_event, Condition, Event, EventReader, EventWriter, IntoSystemConfigs, KeyCode,
+    NextState, Res, ResMut,
+};
+use hive_bevy::data::enums::{AppState, InsectType};
+use hive_bevy::game_state::GameState;
+use hive_bevy::rule_set::RuleSet;
+use std::path::Path;
+
+/// The [`RuleSet`] new games are played with is read from this file if it exists.
+const RULE_SET_PATH: &str = "rules.ron";
+
+#[derive(Event, Clone, Copy)]
+pub enum NewGameRequest {
+    Restart,
+    /// Adds or removes one of the expansion pieces. Only takes effect for the next new game.
+    ToggleExpansion(InsectType),
+}
+
+fn load_rule_set() -> RuleSet {
+    let path = Path::new(RULE_SET_PATH);
+    if !path.exists() {
+        return RuleSet::default();
+    }
+
+    let rule_set = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| RuleSet::from_ron(&content).map_err(|e| e.to_string()));
+    match rule_set {
+        Ok(rule_set) => {
+            info!("Playing with the rules from {RULE_SET_PATH}");
+            rule_set
+        }
+        Err(e) => {
+            error!("Failed to load {RULE_SET_PATH}: {e}");
+            RuleSet::default()
+        }
+    }
+}
+
+fn s_read_new_game_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut new_game_requests: EventWriter<NewGameRequest>,
+) {
+    if !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::KeyN) {
+        new_game_requests.send(NewGameRequest::Restart);
+    }
+}
+
+fn s_apply_new_game_requests(
+    mut new_game_requests: EventReader<NewGameRequest>,
+    mut rule_set: ResMut<RuleSet>,
+    mut game_state: ResMut<GameState>,
+    mut redo_stack: ResMut<RedoStack>,
+    mut draw_offer: ResMut<DrawOffer>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for request in new_game_requests.read() {
+        match request {
+            NewGameRequest::Restart => {
+                *game_state = GameState::with_rules(rule_set.clone());
+                redo_stack.0.clear();
+                draw_offer.0 = None;
+                next_state.set(AppState::Rebuilding);
+            }
+            NewGameRequest::ToggleExpansion(insect) => {
+                let count = match rule_set.count(*insect) {
+                    0 => 1,
+                    _ => 0,
+                };
+                rule_set.set_count(*insect, count);
+            }
+        }
+    }
+}
+
+pub struct NewGamePlugin;
+
+impl Plugin for NewGamePlugin {
+    fn build(&self, app: &mut App) {
+        let rule_set = load_rule_set();
+        app.insert_resource(GameState::with_rules(rule_set.clone()));
+        app.insert_resource(rule_set);
+        app.add_event::<NewGameRequest>();
+        app.add_systems(
+            Update,
+            (
+                s_read_new_game_keys,
+                s_apply_new_game_requests
+                    .after(s_read_new_game_keys)
+                    .run_if(on_event::<NewGameRequest>())
+                    .run_if(in_state(AppState::Idle).or_else(in_state(AppState::PlayerWon))),
+            ),
+        );
+    }
+}
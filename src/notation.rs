@@ -0,0 +1,447 @@
+//! The move notation used by the Universal Hive Protocol and most other Hive software,
+//! e.g. `wA1 -bQ` for "white's first ant moves to the left of black's queen".
+
+use crate::data::enums::{InsectType, Player};
+use crate::game_state::{GameState, Move, Tile};
+use crate::hex_coordinate::{HexCoordinate, HexDirection, ALL_DIRECTIONS};
+use crate::rule_set::RuleSet;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotationError(pub String);
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+fn insect_letter(insect: InsectType) -> char {
+    match insect {
+        InsectType::Ant => 'A',
+        InsectType::Queen => 'Q',
+        InsectType::Spider => 'S',
+        InsectType::Grasshopper => 'G',
+        InsectType::Beetle => 'B',
+        InsectType::Pillbug => 'P',
+        InsectType::Mosquito => 'M',
+        InsectType::Ladybug => 'L',
+    }
+}
+
+fn insect_from_letter(letter: char) -> Option<InsectType> {
+    match letter {
+        'A' => Some(InsectType::Ant),
+        'Q' => Some(InsectType::Queen),
+        'S' => Some(InsectType::Spider),
+        'G' => Some(InsectType::Grasshopper),
+        'B' => Some(InsectType::Beetle),
+        'P' => Some(InsectType::Pillbug),
+        'M' => Some(InsectType::Mosquito),
+        'L' => Some(InsectType::Ladybug),
+        _ => None,
+    }
+}
+
+/// Pieces every player only has one of are written without a number.
+fn is_single_piece(rules: &RuleSet, insect: InsectType) -> bool {
+    rules.count(insect) == 1
+}
+
+/// Writes `tile` as a piece of a game played with `rules`, e.g. `wQ` or `bA3`.
+pub fn format_tile(rules: &RuleSet, tile: &Tile) -> String {
+    let player = match tile.player {
+        Player::Player1 => 'w',
+        Player::Player2 => 'b',
+    };
+    let insect = insect_letter(tile.insect);
+
+    match is_single_piece(rules, tile.insect) {
+        true => format!("{player}{insect}"),
+        false => format!("{player}{insect}{}", tile.ordinal),
+    }
+}
+
+/// Reads a piece of a game played with `rules`, written like [`format_tile`] does.
+pub fn parse_tile(rules: &RuleSet, s: &str) -> Result<Tile, NotationError> {
+    let error = || NotationError(format!("invalid piece '{s}'"));
+
+    let mut chars = s.chars();
+    let player = match chars.next() {
+        Some('w') => Player::Player1,
+        Some('b') => Player::Player2,
+        _ => return Err(error()),
+    };
+    let insect = chars
+        .next()
+        .and_then(insect_from_letter)
+        .ok_or_else(error)?;
+
+    let ordinal = match chars.as_str() {
+        "" if is_single_piece(rules, insect) => 1,
+        number => number.parse::<u8>().map_err(|_| error())?,
+    };
+    if ordinal == 0 {
+        return Err(error());
+    }
+
+    Ok(Tile {
+        player,
+        insect,
+        ordinal,
+    })
+}
+
+/// The separator for a tile placed in `direction` of the reference piece, and whether it
+/// is written in front of the reference.
+fn direction_separator(direction: &HexDirection) -> (char, bool) {
+    match direction {
+        HexDirection::Right => ('-', false),
+        HexDirection::Left => ('-', true),
+        HexDirection::UpRight => ('/', false),
+        HexDirection::DownLeft => ('/', true),
+        HexDirection::DownRight => ('\\', false),
+        HexDirection::UpLeft => ('\\', true),
+    }
+}
+
+fn direction_from_separator(separator: char, in_front: bool) -> Option<&'static HexDirection> {
+    ALL_DIRECTIONS
+        .into_iter()
+        .find(|direction| direction_separator(direction) == (separator, in_front))
+}
+
+/// Writes `mv` relative to the tiles of `game_state`, which must be the state before the move.
+pub fn format_move(game_state: &GameState, mv: &Move) -> String {
+    let (tile, board, to) = match *mv {
+        Move::Pass => return "pass".to_string(),
+        Move::Place { insect, at } => (
+            game_state.tile_to_place(insect),
+            game_state.board().clone(),
+            at,
+        ),
+        Move::Move { from, to } => (
+            *game_state
+                .board()
+                .get_top(&from)
+                .expect("Moved piece needs to be on the board"),
+            game_state.board().get_without(&from),
+            to,
+        ),
+    };
+
+    let rules = game_state.rules();
+    let tile = format_tile(rules, &tile);
+    if board.is_empty() {
+        return tile;
+    }
+
+    if let Some(below) = board.get_top(&to) {
+        return format!("{tile} {}", format_tile(rules, below));
+    }
+
+    for direction in ALL_DIRECTIONS {
+        let Some(reference) = board.get_top(&to.get_relative(direction)) else {
+            continue;
+        };
+
+        let reference = format_tile(rules, reference);
+        return match direction_separator(direction.get_opposite()) {
+            (separator, true) => format!("{tile} {separator}{reference}"),
+            (separator, false) => format!("{tile} {reference}{separator}"),
+        };
+    }
+
+    panic!("{tile} is moved away from the hive");
+}
+
+/// Reads a move written relative to the tiles of `game_state`. The returned move is not
+/// checked for legality.
+pub fn parse_move(game_state: &GameState, s: &str) -> Result<Move, NotationError> {
+    let s = s.trim();
+    if s.eq_ignore_ascii_case("pass") {
+        return Ok(Move::Pass);
+    }
+
+    let mut parts = s.split_whitespace();
+    let name = parts
+        .next()
+        .ok_or_else(|| NotationError("empty move".to_string()))?;
+    let tile = parse_tile(game_state.rules(), name)?;
+    let reference = parts.next();
+    if parts.next().is_some() {
+        return Err(NotationError(format!("invalid move '{s}'")));
+    }
+
+    let to = match reference {
+        None if game_state.board().is_empty() => HexCoordinate::origin(),
+        None => return Err(NotationError(format!("{s} is missing a position"))),
+        Some(reference) => parse_position(game_state, reference)?,
+    };
+
+    match game_state.find_tile(&tile) {
+        Some(from) if game_state.board().get_top(&from) == Some(&tile) => {
+            Ok(Move::Move { from, to })
+        }
+        Some(_) => Err(NotationError(format!("{name} is covered by another piece"))),
+        None if tile.player != game_state.current_player() => {
+            Err(NotationError(format!("{name} can't be placed this turn")))
+        }
+        None if game_state.tile_to_place(tile.insect) == tile => Ok(Move::Place {
+            insect: tile.insect,
+            at: to,
+        }),
+        None => Err(NotationError(format!("{name} can't be placed this turn"))),
+    }
+}
+
+/// Writes every move played so far, separated by `;`, e.g. `wS1;bG1 -wS1;wA1 wS1/`.
+pub fn format_game(game_state: &GameState) -> String {
+    let mut replay = game_state.initial();
+    let mut moves = vec![];
+
+    for mv in game_state.history() {
+        moves.push(format_move(&replay, &mv));
+        replay.apply(mv);
+    }
+
+    moves.join(";")
+}
+
+/// Plays the `;` separated moves of a game record such as the one written by [`format_game`].
+pub fn play_moves(game_state: &mut GameState, moves: &str) -> Result<(), NotationError> {
+    for move_string in moves.split(';').map(str::trim).filter(|m| !m.is_empty()) {
+        let mv = parse_move(game_state, move_string)?;
+        game_state
+            .try_apply(mv)
+            .map_err(|_| NotationError(format!("{move_string} is not a legal move")))?;
+    }
+
+    Ok(())
+}
+
+/// A new game with the default rules and `moves` played, for tests.
+#[cfg(test)]
+pub(crate) fn play(moves: &str) -> GameState {
+    let mut game_state = GameState::new();
+    play_moves(&mut game_state, moves).unwrap();
+    game_state
+}
+
+/// The tile called `name` in a game with the default rules, for tests.
+#[cfg(test)]
+pub(crate) fn tile(name: &str) -> Tile {
+    parse_tile(&RuleSet::default(), name).unwrap()
+}
+
+/// White can surround the black queen in one move.
+#[cfg(test)]
+pub(crate) const WINNING_POSITION: &str = "wQ;bQ wQ/;wA1 /wQ;bQ wQ-;wG1 \\wQ;bS1 bQ\\;wA1 /bS1;bG1 bS1-;wA2 -wA1;bG2 \\bG1;wB1 -wG1;bG1 /bQ;wA2 \\bQ;bA1 bG2-";
+
+fn parse_position(game_state: &GameState, reference: &str) -> Result<HexCoordinate, NotationError> {
+    let (direction, reference) = match (reference.chars().next(), reference.chars().last()) {
+        (Some(separator @ ('-' | '/' | '\\')), _) => {
+            (direction_from_separator(separator, true), &reference[1..])
+        }
+        (_, Some(separator @ ('-' | '/' | '\\'))) => (
+            direction_from_separator(separator, false),
+            &reference[..reference.len() - 1],
+        ),
+        _ => (None, reference),
+    };
+
+    let reference_tile = parse_tile(game_state.rules(), reference)?;
+    let position = game_state
+        .find_tile(&reference_tile)
+        .ok_or_else(|| NotationError(format!("{reference} is not in the hive")))?;
+
+    Ok(match direction {
+        Some(direction) => position.get_relative(direction),
+        None => position,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::enums::InsectType::{Ant, Beetle, Grasshopper, Mosquito, Queen, Spider};
+    use crate::hex_coordinate::HexDirection::{DownLeft, DownRight, Left, Right, UpLeft, UpRight};
+
+    /// A small deterministic generator so the random games are the same on every run.
+    fn next_random(seed: &mut u64) -> usize {
+        *seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (*seed >> 33) as usize
+    }
+
+    #[test]
+    fn tile_names_round_trip() {
+        let rules = RuleSet::default();
+        for name in ["wQ", "bQ", "wA1", "bA4", "wS3", "bG2", "wB1", "bB2", "wP"] {
+            assert_eq!(format_tile(&rules, &tile(name)), name);
+        }
+
+        assert_eq!(format_tile(&rules, &tile("bQ1")), "bQ");
+
+        for invalid in ["", "w", "xA1", "wX1", "wA", "wA0", "wAx"] {
+            assert!(
+                parse_tile(&rules, invalid).is_err(),
+                "{invalid} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn pieces_are_numbered_if_there_are_several() {
+        let rules = RuleSet::from_game_type("Base+M").unwrap();
+        let mut two_mosquitoes = rules.clone();
+        two_mosquitoes.set_count(Mosquito, 2);
+        two_mosquitoes.set_count(Queen, 2);
+
+        let mosquito = parse_tile(&rules, "wM").unwrap();
+        assert_eq!(format_tile(&rules, &mosquito), "wM");
+        assert_eq!(format_tile(&two_mosquitoes, &mosquito), "wM1");
+        assert!(parse_tile(&two_mosquitoes, "wM").is_err());
+        assert_eq!(parse_tile(&two_mosquitoes, "wM1"), Ok(mosquito));
+
+        let mut game_state = GameState::with_rules(two_mosquitoes);
+        let moves = "wM1;bM1 wM1-;wQ1 -wM1;bQ1 bM1-;wM2 /wM1;bQ2 bQ1/";
+        play_moves(&mut game_state, moves).unwrap();
+        assert_eq!(format_game(&game_state), moves);
+    }
+
+    #[test]
+    fn parses_all_directions() {
+        let game_state = play("wS1;bS1 -wS1");
+        let spider = game_state.find_tile(&tile("wS1")).unwrap();
+
+        let expected = [
+            ("wQ wS1-", Right),
+            ("wQ wS1/", UpRight),
+            ("wQ wS1\\", DownRight),
+        ];
+        for (move_string, direction) in expected {
+            assert_eq!(
+                parse_move(&game_state, move_string),
+                Ok(Move::Place {
+                    insect: Queen,
+                    at: spider.get_relative(&direction),
+                })
+            );
+        }
+
+        let black_spider = game_state.find_tile(&tile("bS1")).unwrap();
+        let expected = [
+            ("wQ -bS1", Left),
+            ("wQ /bS1", DownLeft),
+            ("wQ \\bS1", UpLeft),
+        ];
+        for (move_string, direction) in expected {
+            assert_eq!(
+                parse_move(&game_state, move_string),
+                Ok(Move::Place {
+                    insect: Queen,
+                    at: black_spider.get_relative(&direction),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn parses_moves_onto_the_hive() {
+        let game_state = play("wB1;bB1 wB1-;wQ -wB1;bQ bB1-");
+
+        let white_beetle = game_state.find_tile(&tile("wB1")).unwrap();
+        let black_beetle = game_state.find_tile(&tile("bB1")).unwrap();
+        assert_eq!(
+            parse_move(&game_state, "wB1 bB1"),
+            Ok(Move::Move {
+                from: white_beetle,
+                to: black_beetle,
+            })
+        );
+        assert_eq!(
+            format_move(
+                &game_state,
+                &Move::Move {
+                    from: white_beetle,
+                    to: black_beetle,
+                }
+            ),
+            "wB1 bB1"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_moves() {
+        let game_state = play("wS1;bS1 -wS1");
+
+        for invalid in [
+            "",
+            "bQ wS1-",
+            "wS3 wS1-",
+            "wQ",
+            "wQ wA1-",
+            "wQ wS1- x",
+            "wQ wS1|",
+        ] {
+            assert!(
+                parse_move(&game_state, invalid).is_err(),
+                "{invalid} should not parse"
+            );
+        }
+        assert_eq!(parse_move(&game_state, "pass"), Ok(Move::Pass));
+    }
+
+    #[test]
+    fn ordinals_count_placed_pieces() {
+        let game_state = play("wA1;bA1 wA1-;wA2 -wA1;bA2 bA1-;wQ /wA1");
+        assert_eq!(
+            format_game(&game_state),
+            "wA1;bA1 wA1-;wA2 -wA1;bA2 bA1-;wQ /wA1"
+        );
+        assert_eq!(
+            format_tile(game_state.rules(), &game_state.tile_to_place(Ant)),
+            "bA3"
+        );
+        for insect in [Spider, Beetle, Grasshopper] {
+            assert_eq!(game_state.tile_to_place(insect).ordinal, 1);
+        }
+    }
+
+    #[test]
+    fn random_games_round_trip() {
+        let mut seed = 7;
+
+        for _ in 0..20 {
+            let mut game_state = GameState::new();
+
+            for _ in 0..40 {
+                let moves = game_state.legal_moves();
+                if moves.is_empty() {
+                    break;
+                }
+
+                for mv in &moves {
+                    let move_string = format_move(&game_state, mv);
+                    assert_eq!(
+                        parse_move(&game_state, &move_string),
+                        Ok(*mv),
+                        "{move_string}"
+                    );
+                }
+
+                let mv = moves[next_random(&mut seed) % moves.len()];
+                game_state.apply(mv);
+            }
+
+            let record = format_game(&game_state);
+            let replayed = play(&record);
+            assert_eq!(replayed.board(), game_state.board());
+            assert_eq!(format_game(&replayed), record);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::history::RedoStack;
+use bevy::app::{App, Plugin, Update};
+use bevy::log::info;
+use bevy::prelude::{
+    in_state, not, resource_exists, Commands, IntoSystemConfigs, NextState, OnExit, Res, ResMut,
+    Resource,
+};
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
+use hive_bevy::ai::SearchSettings;
+use hive_bevy::bot::Bot;
+use hive_bevy::data::enums::{AppState, Player};
+use hive_bevy::game_state::{GameState, Move};
+use hive_bevy::mcts::MctsSettings;
+use hive_bevy::notation::format_move;
+
+/// Who makes the moves for a player.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Controller {
+    Human,
+    Computer(Bot),
+}
+
+impl Controller {
+    /// The next controller when cycling through them in the UI.
+    pub fn next(&self) -> Controller {
+        match self {
+            Controller::Human => Controller::Computer(Bot::AlphaBeta(SearchSettings::default())),
+            Controller::Computer(Bot::AlphaBeta(_)) => {
+                Controller::Computer(Bot::Mcts(MctsSettings::default()))
+            }
+            Controller::Computer(_) => Controller::Human,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct PlayerControllers {
+    pub player1: Controller,
+    pub player2: Controller,
+}
+
+impl Default for PlayerControllers {
+    fn default() -> Self {
+        PlayerControllers {
+            player1: Controller::Human,
+            player2: Controller::Human,
+        }
+    }
+}
+
+impl PlayerControllers {
+    pub fn get(&self, player: Player) -> Controller {
+        match player {
+            Player::Player1 => self.player1,
+            Player::Player2 => self.player2,
+        }
+    }
+
+    pub fn get_mut(&mut self, player: Player) -> &mut Controller {
+        match player {
+            Player::Player1 => &mut self.player1,
+            Player::Player2 => &mut self.player2,
+        }
+    }
+
+    pub fn is_human(&self, player: Player) -> bool {
+        matches!(self.get(player), Controller::Human)
+    }
+}
+
+/// The search for the next computer move, running on the [`AsyncComputeTaskPool`] so the frame
+/// loop keeps going while the computer thinks.
+#[derive(Resource)]
+struct PendingMove(Task<Move>);
+
+fn s_start_computer_move(
+    controllers: Res<PlayerControllers>,
+    game_state: Res<GameState>,
+    mut commands: Commands,
+) {
+    let bot = match controllers.get(game_state.current_player()) {
+        Controller::Human => return,
+        Controller::Computer(bot) => bot,
+    };
+
+    let game_state = game_state.clone();
+    let task = AsyncComputeTaskPool::get().spawn(async move { bot.best_move(&game_state) });
+    commands.insert_resource(PendingMove(task));
+}
+
+fn s_finish_computer_move(
+    controllers: Res<PlayerControllers>,
+    mut pending_move: ResMut<PendingMove>,
+    mut game_state: ResMut<GameState>,
+    mut redo_stack: ResMut<RedoStack>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut commands: Commands,
+) {
+    let Some(mv) = block_on(poll_once(&mut pending_move.0)) else {
+        return;
+    };
+    commands.remove_resource::<PendingMove>();
+
+    //the player might have taken over while the computer was thinking
+    if controllers.is_human(game_state.current_player()) {
+        return;
+    }
+
+    info!(
+        "{:?} played {}",
+        game_state.current_player(),
+        format_move(&game_state, &mv)
+    );
+    game_state.apply(mv);
+    redo_stack.0.clear();
+    next_state.set(AppState::Rebuilding);
+}
+
+/// Drops the search when the position changed in the meantime, e.g. because of an undo.
+fn s_cancel_computer_move(mut commands: Commands) {
+    commands.remove_resource::<PendingMove>();
+}
+
+pub struct OpponentPlugin;
+
+impl Plugin for OpponentPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerControllers>();
+        app.add_systems(
+            Update,
+            (
+                s_start_computer_move.run_if(not(resource_exists::<PendingMove>)),
+                s_finish_computer_move.run_if(resource_exists::<PendingMove>),
+            )
+                .run_if(in_state(AppState::Idle)),
+        );
+        app.add_systems(OnExit(AppState::Idle), s_cancel_computer_move);
+    }
+}
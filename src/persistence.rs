@@ -0,0 +1,129 @@
+use crate::history::RedoStack;
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::input::ButtonInput;
+use bevy::log::{error, info};
+use bevy::prelude::{
+    in_state, on_event, Condition, Event, EventReader, EventWriter, IntoSystemConfigs, KeyCode,
+    NextState, Res, ResMut, Resource,
+};
+use hive_bevy::data::enums::AppState;
+use hive_bevy::game_state::GameState;
+use hive_bevy::save_game::{load_from_file, save_to_file};
+use std::path::{Path, PathBuf};
+
+const DEFAULT_SAVE_PATH: &str = "hive_save.ron";
+
+/// The file games are saved to and loaded from. Can be set as the first command line argument,
+/// in which case that game is loaded on startup, and changes to the file picked when loading.
+#[derive(Resource)]
+pub struct SaveGamePath(pub PathBuf);
+
+#[derive(Event, Clone, Copy)]
+pub enum SaveGameRequest {
+    Save,
+    Load,
+}
+
+fn s_load_game_on_startup(path: Res<SaveGamePath>, mut game_state: ResMut<GameState>) {
+    match load_from_file(&path.0) {
+        Ok(loaded) => *game_state = loaded,
+        Err(e) => error!("Failed to load {}: {e}", path.0.display()),
+    }
+}
+
+/// Asks which game to load, starting in the folder of the current save file. Besides saved
+/// games any file can be picked, which [`load_from_file`] then reads as a game record.
+#[cfg(not(target_arch = "wasm32"))]
+fn pick_game_to_load(current: &Path) -> Option<PathBuf> {
+    let folder = current
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    rfd::FileDialog::new()
+        .set_title("Load game")
+        .add_filter("Hive game", &["ron"])
+        .add_filter("Game record", &["txt"])
+        .add_filter("All files", &["*"])
+        .set_directory(folder)
+        .pick_file()
+}
+
+/// The web has no file picker, so the current save file is loaded again.
+#[cfg(target_arch = "wasm32")]
+fn pick_game_to_load(current: &Path) -> Option<PathBuf> {
+    Some(current.to_path_buf())
+}
+
+fn s_read_save_game_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut save_game_requests: EventWriter<SaveGameRequest>,
+) {
+    if !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::KeyS) {
+        save_game_requests.send(SaveGameRequest::Save);
+    }
+    if keys.just_pressed(KeyCode::KeyO) {
+        save_game_requests.send(SaveGameRequest::Load);
+    }
+}
+
+fn s_apply_save_game_requests(
+    mut save_game_requests: EventReader<SaveGameRequest>,
+    mut path: ResMut<SaveGamePath>,
+    mut game_state: ResMut<GameState>,
+    mut redo_stack: ResMut<RedoStack>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for request in save_game_requests.read() {
+        match request {
+            SaveGameRequest::Save => match save_to_file(&game_state, &path.0) {
+                Ok(()) => info!("Saved game to {}", path.0.display()),
+                Err(e) => error!("Failed to save {}: {e}", path.0.display()),
+            },
+            SaveGameRequest::Load => {
+                let Some(picked) = pick_game_to_load(&path.0) else {
+                    continue;
+                };
+                match load_from_file(&picked) {
+                    Ok(loaded) => {
+                        *game_state = loaded;
+                        redo_stack.0.clear();
+                        next_state.set(AppState::Rebuilding);
+                        path.0 = picked;
+                    }
+                    Err(e) => error!("Failed to load {}: {e}", picked.display()),
+                }
+            }
+        }
+    }
+}
+
+pub struct PersistencePlugin;
+
+impl Plugin for PersistencePlugin {
+    fn build(&self, app: &mut App) {
+        let path = std::env::args().nth(1);
+        if path.is_some() {
+            app.add_systems(Startup, s_load_game_on_startup);
+        }
+
+        app.insert_resource(SaveGamePath(
+            path.map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_SAVE_PATH)),
+        ));
+        app.add_event::<SaveGameRequest>();
+        app.add_systems(
+            Update,
+            (
+                s_read_save_game_keys,
+                s_apply_save_game_requests
+                    .after(s_read_save_game_keys)
+                    .run_if(on_event::<SaveGameRequest>())
+                    .run_if(in_state(AppState::Idle).or_else(in_state(AppState::PlayerWon))),
+            ),
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use crate::world_cursor::WorldCursor;
+use bevy::app::{App, Plugin, Update};
+use bevy::input::ButtonInput;
+use bevy::prelude::{
+    in_state, on_event, Commands, DetectChangesMut, Event, EventReader, EventWriter,
+    IntoSystemConfigs, KeyCode, NextState, Res, ResMut, Resource, State, Time, Timer, TimerMode,
+};
+use hive_bevy::data::enums::AppState;
+use hive_bevy::game_state::GameState;
+use std::time::Duration;
+
+const MIN_SECONDS_PER_PLY: f32 = 0.125;
+const MAX_SECONDS_PER_PLY: f32 = 8.;
+
+/// The game being reviewed. While replaying, the [`GameState`] resource holds the position
+/// after the first `ply` moves of it.
+#[derive(Resource)]
+pub struct Replay {
+    pub game_state: GameState,
+    pub ply: usize,
+    pub autoplay: bool,
+    pub timer: Timer,
+}
+
+impl Replay {
+    pub fn len(&self) -> usize {
+        self.game_state.history().len()
+    }
+
+    fn position(&self) -> GameState {
+        let mut position = self.game_state.clone();
+        while position.history().len() > self.ply {
+            position.undo();
+        }
+        position
+    }
+}
+
+#[derive(Event, Clone, Copy)]
+pub enum ReplayRequest {
+    Toggle,
+    First,
+    Back,
+    Forward,
+    Last,
+    JumpTo(usize),
+    ToggleAutoplay,
+    Faster,
+    Slower,
+}
+
+fn s_read_replay_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut replay_requests: EventWriter<ReplayRequest>,
+) {
+    let bindings = [
+        (KeyCode::KeyR, ReplayRequest::Toggle),
+        (KeyCode::Home, ReplayRequest::First),
+        (KeyCode::ArrowLeft, ReplayRequest::Back),
+        (KeyCode::ArrowRight, ReplayRequest::Forward),
+        (KeyCode::End, ReplayRequest::Last),
+        (KeyCode::Space, ReplayRequest::ToggleAutoplay),
+        (KeyCode::Equal, ReplayRequest::Faster),
+        (KeyCode::Minus, ReplayRequest::Slower),
+    ];
+
+    for (key, request) in bindings {
+        if keys.just_pressed(key) {
+            replay_requests.send(request);
+        }
+    }
+}
+
+fn s_apply_replay_requests(
+    mut replay_requests: EventReader<ReplayRequest>,
+    state: Res<State<AppState>>,
+    replay: Option<ResMut<Replay>>,
+    mut game_state: ResMut<GameState>,
+    mut world_cursor: ResMut<WorldCursor>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut commands: Commands,
+) {
+    let Some(mut replay) = replay else {
+        if !matches!(state.get(), AppState::Idle | AppState::PlayerWon) {
+            return;
+        }
+
+        if replay_requests
+            .read()
+            .any(|request| matches!(request, ReplayRequest::Toggle))
+        {
+            let len = game_state.history().len();
+            commands.insert_resource(Replay {
+                game_state: game_state.clone(),
+                ply: len,
+                autoplay: false,
+                timer: Timer::from_seconds(1., TimerMode::Repeating),
+            });
+            //marks the state as changed so the tiles in the inventory get removed
+            game_state.set_changed();
+            world_cursor.disabled = true;
+            next_state.set(AppState::Replay);
+        }
+        return;
+    };
+
+    let ply = replay.ply;
+
+    for request in replay_requests.read() {
+        match *request {
+            ReplayRequest::Toggle => {
+                *game_state = replay.game_state.clone();
+                commands.remove_resource::<Replay>();
+                world_cursor.disabled = false;
+                next_state.set(AppState::Rebuilding);
+                return;
+            }
+            ReplayRequest::First => replay.ply = 0,
+            ReplayRequest::Back => replay.ply = replay.ply.saturating_sub(1),
+            ReplayRequest::Forward => replay.ply = (replay.ply + 1).min(replay.len()),
+            ReplayRequest::Last => replay.ply = replay.len(),
+            ReplayRequest::JumpTo(ply) => replay.ply = ply.min(replay.len()),
+            ReplayRequest::ToggleAutoplay => {
+                replay.autoplay = !replay.autoplay;
+                if replay.autoplay && replay.ply == replay.len() {
+                    replay.ply = 0;
+                }
+            }
+            ReplayRequest::Faster | ReplayRequest::Slower => {
+                let seconds = match request {
+                    ReplayRequest::Faster => replay.timer.duration().as_secs_f32() / 2.,
+                    _ => replay.timer.duration().as_secs_f32() * 2.,
+                };
+                replay.timer.set_duration(Duration::from_secs_f32(
+                    seconds.clamp(MIN_SECONDS_PER_PLY, MAX_SECONDS_PER_PLY),
+                ));
+            }
+        }
+    }
+
+    if replay.ply != ply {
+        *game_state = replay.position();
+    }
+}
+
+fn s_autoplay_replay(
+    time: Res<Time>,
+    mut replay: ResMut<Replay>,
+    mut game_state: ResMut<GameState>,
+) {
+    if !replay.autoplay || !replay.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    if replay.ply == replay.len() {
+        replay.autoplay = false;
+        return;
+    }
+
+    replay.ply += 1;
+    *game_state = replay.position();
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ReplayRequest>();
+        app.add_systems(
+            Update,
+            (
+                s_read_replay_keys,
+                s_apply_replay_requests
+                    .after(s_read_replay_keys)
+                    .run_if(on_event::<ReplayRequest>()),
+                s_autoplay_replay.run_if(in_state(AppState::Replay)),
+            ),
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Which pieces a game is played with and the options that differ between house rules and
+//! tournaments. Rule sets can be written in RON, where missing fields keep their default:
+//!
+//! ```ron
+//! (
+//!     pieces: [(Queen, 1), (Ant, 3), (Spider, 2), (Beetle, 2), (Grasshopper, 3), (Mosquito, 1)],
+//!     queen_deadline: 4,
+//!     no_queen_first: true,
+//! )
+//! ```
+
+use crate::data::components::PlayerInventory;
+use crate::data::enums::InsectType;
+use crate::data::enums::InsectType::{
+    Ant, Beetle, Grasshopper, Ladybug, Mosquito, Pillbug, Queen, Spider,
+};
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+
+/// The pieces that are not part of the base game, in the order of their UHP game type letters.
+pub const EXPANSIONS: [InsectType; 3] = [Mosquito, Ladybug, Pillbug];
+
+const BASE_GAME: &str = "Base";
+
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleSet {
+    /// How many pieces of each insect every player starts with, in the order they are offered.
+    /// Insects that are not listed are not in play.
+    pub pieces: Vec<(InsectType, u8)>,
+    /// The own turn by which the queen has to be placed, counting from 1.
+    pub queen_deadline: u32,
+    /// The tournament rule that the queen can't be the first piece of a player.
+    pub no_queen_first: bool,
+    /// Draws the game once both players made this many moves.
+    pub move_limit: Option<u32>,
+}
+
+/// The pieces this game started out with, four ants and three spiders among them, plus the
+/// pillbug. Unlike [`RuleSet::base_game`], this is not the official set.
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet {
+            pieces: vec![
+                (Ant, 4),
+                (Queen, 1),
+                (Spider, 3),
+                (Beetle, 2),
+                (Grasshopper, 3),
+                (Pillbug, 1),
+            ],
+            queen_deadline: 3,
+            no_queen_first: false,
+            move_limit: None,
+        }
+    }
+}
+
+impl RuleSet {
+    /// The official base game, as expected by other UHP software.
+    pub fn base_game() -> RuleSet {
+        RuleSet {
+            pieces: vec![
+                (Queen, 1),
+                (Spider, 2),
+                (Beetle, 2),
+                (Grasshopper, 3),
+                (Ant, 3),
+            ],
+            queen_deadline: 4,
+            no_queen_first: true,
+            move_limit: None,
+        }
+    }
+
+    /// The rules for a UHP game type such as `Base` or `Base+MP`, where each letter after the
+    /// `+` adds one of the [`EXPANSIONS`].
+    pub fn from_game_type(game_type: &str) -> Option<RuleSet> {
+        let expansions = match game_type.split_once('+') {
+            None if game_type == BASE_GAME => "",
+            Some((BASE_GAME, expansions)) if !expansions.is_empty() => expansions,
+            _ => return None,
+        };
+
+        let mut rule_set = RuleSet::base_game();
+        for letter in expansions.chars() {
+            let expansion = match letter {
+                'M' => Mosquito,
+                'L' => Ladybug,
+                'P' => Pillbug,
+                _ => return None,
+            };
+            if rule_set.count(expansion) > 0 {
+                return None;
+            }
+            rule_set.set_count(expansion, 1);
+        }
+
+        Some(rule_set)
+    }
+
+    pub fn from_ron(s: &str) -> Result<RuleSet, ron::error::SpannedError> {
+        ron::from_str(s)
+    }
+
+    pub fn count(&self, insect: InsectType) -> u8 {
+        self.pieces
+            .iter()
+            .find(|(i, _)| *i == insect)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    /// Changes how many pieces of `insect` are in play, where 0 takes it out of the game.
+    pub fn set_count(&mut self, insect: InsectType, count: u8) {
+        match self.pieces.iter_mut().find(|(i, _)| *i == insect) {
+            Some(entry) => entry.1 = count,
+            None => self.pieces.push((insect, count)),
+        }
+        self.pieces.retain(|(_, count)| *count > 0);
+    }
+
+    /// The pieces every player starts with.
+    pub fn inventory(&self) -> PlayerInventory {
+        PlayerInventory {
+            pieces: self
+                .pieces
+                .iter()
+                .flat_map(|(insect, count)| std::iter::repeat_n(*insect, *count as usize))
+                .collect(),
+            moves_played: 0,
+        }
+    }
+
+    /// Which of `pieces` may be placed on the turn after `moves_played` own moves.
+    pub fn allowed_placements(&self, pieces: &[InsectType], moves_played: u32) -> Vec<InsectType> {
+        if !pieces.contains(&Queen) {
+            return pieces.to_vec();
+        }
+
+        if moves_played + 1 >= self.queen_deadline {
+            return vec![Queen];
+        }
+
+        match self.no_queen_first && moves_played == 0 {
+            true => pieces.iter().copied().filter(|i| *i != Queen).collect(),
+            false => pieces.to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_types_add_expansions() {
+        let rule_set = RuleSet::from_game_type("Base+MP").unwrap();
+        assert_eq!(rule_set.count(Mosquito), 1);
+        assert_eq!(rule_set.count(Pillbug), 1);
+        assert_eq!(rule_set.count(Ladybug), 0);
+        assert_eq!(rule_set.inventory().pieces.len(), 13);
+
+        for game_type in ["", "Base+", "Base+X", "Base+MM", "Extended"] {
+            assert_eq!(RuleSet::from_game_type(game_type), None, "{game_type}");
+        }
+    }
+
+    #[test]
+    fn loads_from_ron_with_defaults() {
+        let rule_set =
+            RuleSet::from_ron("(pieces: [(Queen, 1), (Ant, 2)], no_queen_first: true)").unwrap();
+        assert_eq!(rule_set.inventory().pieces, [Queen, Ant, Ant]);
+        assert_eq!(rule_set.queen_deadline, RuleSet::default().queen_deadline);
+        assert!(rule_set.no_queen_first);
+    }
+
+    #[test]
+    fn queen_placement_rules() {
+        let rule_set = RuleSet {
+            queen_deadline: 4,
+            no_queen_first: true,
+            ..RuleSet::default()
+        };
+        let pieces = [Ant, Queen, Spider];
+
+        assert_eq!(rule_set.allowed_placements(&pieces, 0), [Ant, Spider]);
+        assert_eq!(rule_set.allowed_placements(&pieces, 2), pieces);
+        assert_eq!(rule_set.allowed_placements(&pieces, 3), [Queen]);
+        assert_eq!(rule_set.allowed_placements(&[Ant], 3), [Ant]);
+    }
+}
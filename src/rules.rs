@@ -1,119 +1,150 @@
-use crate::data::components::{
-    CurrentPlayer, GameAssets, HasTileOnTop, IsInGame, IsOnTopOf, Level, PositionCache,
-    PossiblePlacementMarker, SelectedTile,
-};
 use crate::data::enums::{InsectType, Player};
+use crate::game_state::{Board, LiftedBoard};
 use crate::hex_coordinate::{HexCoordinate, ALL_DIRECTIONS};
-use bevy::math::Vec3;
-use bevy::prelude::{default, Commands, Entity, Query, Res, With, Without};
-use bevy::sprite::MaterialMesh2dBundle;
-use bevy::utils::HashSet;
-
-pub fn s_spawn_placement_markers(
-    position_cache: Res<PositionCache>,
-    q_player: Query<&Player, (With<IsInGame>, Without<HasTileOnTop>)>,
-    q_insect: Query<&InsectType>,
-    q_hex_coord: Query<&HexCoordinate, With<IsInGame>>,
-    q_is_hive_tile: Query<(), With<IsInGame>>,
-    q_is_topmost: Query<(), (With<IsOnTopOf>, Without<HasTileOnTop>)>,
-    game_assets: Res<GameAssets>,
-    current_player: Res<CurrentPlayer>,
-    selected_tile: Res<SelectedTile>,
-    mut commands: Commands,
-    q_is_on_top_of: Query<&IsOnTopOf>,
-) {
-    let is_new_piece = !q_is_hive_tile.contains(selected_tile.0);
+use bevy::utils::{HashMap, HashSet};
 
-    let valid_moves;
-    if is_new_piece {
-        let player_has_tile_in_game = q_player.iter().any(|p| *p == current_player.player);
+/// Returns every position the topmost tile at `position` may move to.
+pub fn get_moves_for_tile(board: &Board, position: HexCoordinate) -> Vec<HexCoordinate> {
+    get_moves_for_tile_with_pinned(board, position, &get_pinned_positions(board))
+}
 
-        valid_moves = get_moves_for_new_piece(
-            position_cache,
-            current_player.player,
-            !player_has_tile_in_game,
-        );
-    } else {
-        let insect_type = q_insect.get(selected_tile.0).unwrap();
+/// Like [`get_moves_for_tile`], for callers that look at many tiles of the same board and only
+/// want to find the pinned ones once, see [`get_pinned_positions`].
+pub fn get_moves_for_tile_with_pinned(
+    board: &Board,
+    position: HexCoordinate,
+    pinned: &HashSet<HexCoordinate>,
+) -> Vec<HexCoordinate> {
+    let Some(tile) = board.get_top(&position) else {
+        return vec![];
+    };
 
-        let selected_tile_position = *q_hex_coord.get(selected_tile.0).unwrap();
+    if pinned.contains(&position) {
+        return vec![];
+    }
 
-        let position_cache_without_selected = position_cache.get_without(&selected_tile_position);
+    let is_on_top = board.height(&position) > 1;
+    let lifted = board.lifted(position);
+
+    let mut moves = match tile.insect {
+        InsectType::Mosquito if is_on_top => get_moves_for_beetle(&lifted, position),
+        InsectType::Mosquito => get_copied_insects(board, position)
+            .into_iter()
+            .flat_map(|insect| get_moves_for_insect(&lifted, position, insect))
+            .collect(),
+        insect => get_moves_for_insect(&lifted, position, insect),
+    };
+
+    //different paths can lead to the same destination
+    let mut unique = HashSet::new();
+    moves.retain(|destination| *destination != position && unique.insert(*destination));
+    moves
+}
 
-        if !q_is_on_top_of.contains(selected_tile.0) {
-            if !check_moving_piece_allowed(&position_cache_without_selected) {
-                return;
-            }
-        }
+fn get_moves_for_insect(
+    board: &LiftedBoard,
+    position: HexCoordinate,
+    insect: InsectType,
+) -> Vec<HexCoordinate> {
+    match insect {
+        InsectType::Ant => get_moves_for_ant(board, position),
+        InsectType::Queen => get_moves_for_queen(board, position),
+        InsectType::Spider => get_moves_for_spider(board, position),
+        InsectType::Grasshopper => get_moves_for_grasshopper(board, position),
+        InsectType::Beetle => get_moves_for_beetle(board, position),
+        InsectType::Pillbug => get_moves_for_queen(board, position),
+        InsectType::Ladybug => get_moves_for_ladybug(board, position),
+        //copying another mosquito doesn't give a mosquito any moves
+        InsectType::Mosquito => vec![],
+    }
+}
 
-        valid_moves = match insect_type {
-            InsectType::Ant => {
-                get_moves_for_ant(position_cache_without_selected, selected_tile_position)
-            }
-            InsectType::Queen => {
-                get_moves_for_queen(position_cache_without_selected, selected_tile_position)
-            }
-            InsectType::Spider => {
-                get_moves_for_spider(position_cache_without_selected, selected_tile_position)
+/// The insects a mosquito on the ground at `position` can copy, which are the topmost tiles
+/// next to it.
+pub fn get_copied_insects(board: &Board, position: HexCoordinate) -> Vec<InsectType> {
+    let mut insects = vec![];
+    for neighbour in ALL_DIRECTIONS.map(|dir| position.get_relative(dir)) {
+        if let Some(tile) = board.get_top(&neighbour) {
+            if !insects.contains(&tile.insect) {
+                insects.push(tile.insect);
             }
-            InsectType::Grasshopper => {
-                get_moves_for_grasshopper(position_cache_without_selected, selected_tile_position)
-            }
-            InsectType::Beetle => get_moves_for_beetle(
-                position_cache_without_selected,
-                selected_tile_position,
-                q_is_topmost,
-                selected_tile.0,
-            ),
         }
     }
-
-    for valid_move in valid_moves {
-        let bundle = PossiblePlacementMarker {
-            renderer: MaterialMesh2dBundle {
-                mesh: game_assets.mesh.clone(),
-                material: game_assets.color_materials.grey.clone(),
-                transform: valid_move
-                    .get_transform(&Level(0), -2.)
-                    .with_scale(Vec3::new(1.2, 1.2, 1.2)),
-                ..default()
-            },
-            possible_placement_tag: Default::default(),
-            hex_coordinate: valid_move,
-        };
-        commands.spawn(bundle);
-    }
+    insects
 }
 
-fn get_moves_for_queen(
-    position_cache: PositionCache,
-    current_position: HexCoordinate,
-) -> Vec<HexCoordinate> {
-    position_cache.get_surrounding_slidable_tiles(current_position, &vec![])
+fn get_moves_for_queen(board: &LiftedBoard, current_position: HexCoordinate) -> Vec<HexCoordinate> {
+    board.get_surrounding_slidable_tiles(current_position, &[])
 }
 
+/// One step in any direction, onto or down from the hive as well. Up there the beetle still
+/// can't squeeze between two stacks that are higher than where it comes from and goes to.
 fn get_moves_for_beetle(
-    position_cache: PositionCache,
+    board: &LiftedBoard,
     current_position: HexCoordinate,
-    q_is_topmost: Query<(), (With<IsOnTopOf>, Without<HasTileOnTop>)>,
-    entity: Entity,
 ) -> Vec<HexCoordinate> {
-    let can_move_to_empty = q_is_topmost.contains(entity);
+    let height = board.height(&current_position);
+    if height == 0 {
+        //on ground level the beetle can either slide like the queen or climb onto the hive
+        let mut result = board.get_surrounding_slidable_tiles(current_position, &[]);
+        for potential_move in ALL_DIRECTIONS.map(|dir| current_position.get_relative(dir)) {
+            if board.contains(&potential_move)
+                && !is_blocked_by_gate(
+                    board,
+                    current_position,
+                    potential_move,
+                    board.height(&potential_move),
+                )
+            {
+                result.push(potential_move);
+            }
+        }
+        return result;
+    }
+
+    ALL_DIRECTIONS
+        .map(|dir| current_position.get_relative(dir))
+        .into_iter()
+        .filter(|to| {
+            let passing_height = height.max(board.height(to));
+            !is_blocked_by_gate(board, current_position, *to, passing_height)
+        })
+        .collect()
+}
 
-    let mut result = vec![];
+/// Two steps on top of the hive, like a beetle would climb, followed by one step down.
+fn get_moves_for_ladybug(board: &LiftedBoard, start_position: HexCoordinate) -> Vec<HexCoordinate> {
+    let neighbours = |position: HexCoordinate| ALL_DIRECTIONS.map(|dir| position.get_relative(dir));
+    let mut possible_moves = vec![];
 
-    for potential_move in ALL_DIRECTIONS.map(|dir| current_position.get_relative(dir)) {
-        if !can_move_to_empty && !position_cache.0.contains_key(&potential_move) {
+    for first in neighbours(start_position) {
+        if !board.contains(&first)
+            || is_blocked_by_gate(board, start_position, first, board.height(&first))
+        {
             continue;
         }
-        result.push(potential_move);
+
+        for second in neighbours(first) {
+            let height = board.height(&first).max(board.height(&second));
+            if !board.contains(&second) || is_blocked_by_gate(board, first, second, height) {
+                continue;
+            }
+
+            for destination in neighbours(second) {
+                if !board.contains(&destination)
+                    && !is_blocked_by_gate(board, second, destination, board.height(&second))
+                {
+                    possible_moves.push(destination);
+                }
+            }
+        }
     }
 
-    result
+    possible_moves
 }
 
 fn get_moves_for_grasshopper(
-    position_cache: PositionCache,
+    board: &LiftedBoard,
     start_position: HexCoordinate,
 ) -> Vec<HexCoordinate> {
     let mut possible_moves = vec![];
@@ -124,7 +155,7 @@ fn get_moves_for_grasshopper(
         loop {
             let new_position = position.get_relative(direction);
 
-            if !position_cache.0.contains_key(&new_position) {
+            if !board.contains(&new_position) {
                 if new_position != start_position && at_lest_one_jump {
                     possible_moves.push(new_position);
                 }
@@ -140,77 +171,123 @@ fn get_moves_for_grasshopper(
     possible_moves
 }
 
-fn get_moves_for_ant(
-    position_cache: PositionCache,
-    start_position: HexCoordinate,
-) -> Vec<HexCoordinate> {
-    let mut possible_moves = position_cache.get_surrounding_slidable_tiles(start_position, &vec![]);
-
-    loop {
-        let mut new_moves = vec![];
+fn get_moves_for_ant(board: &LiftedBoard, start_position: HexCoordinate) -> Vec<HexCoordinate> {
+    get_slide_destinations(board, start_position, None)
+}
 
-        let mut ignore = possible_moves.clone();
-        ignore.push(start_position);
+fn get_moves_for_spider(board: &LiftedBoard, start_position: HexCoordinate) -> Vec<HexCoordinate> {
+    get_slide_destinations(board, start_position, Some(3))
+}
 
-        for existing_move in &possible_moves {
-            for new_move in position_cache.get_surrounding_slidable_tiles(*existing_move, &ignore) {
-                new_moves.push(new_move);
+/// Where a tile can get to by sliding along the edge of the hive, which is `board` without
+/// the tile itself. With `steps`, the tile slides exactly that often and doesn't visit a position
+/// twice on the way, otherwise it can slide as far as it likes.
+fn get_slide_destinations(
+    board: &LiftedBoard,
+    start_position: HexCoordinate,
+    steps: Option<usize>,
+) -> Vec<HexCoordinate> {
+    let Some(steps) = steps else {
+        //any number of slides can reach every position connected to the start
+        let mut reached = vec![start_position];
+        let mut index = 0;
+        while let Some(position) = reached.get(index).copied() {
+            for next in board.get_surrounding_slidable_tiles(position, &reached) {
+                reached.push(next);
             }
+            index += 1;
         }
+        reached.remove(0);
+        return reached;
+    };
+
+    let mut destinations = vec![];
+    let mut path = vec![start_position];
+    extend_slide_path(board, &mut path, steps, &mut destinations);
+    destinations
+}
 
-        if new_moves.len() == 0 {
-            break;
-        }
-
-        for new_move in new_moves {
-            possible_moves.push(new_move);
+fn extend_slide_path(
+    board: &LiftedBoard,
+    path: &mut Vec<HexCoordinate>,
+    steps: usize,
+    destinations: &mut Vec<HexCoordinate>,
+) {
+    let position = *path.last().unwrap();
+    if path.len() > steps {
+        if !destinations.contains(&position) {
+            destinations.push(position);
         }
+        return;
     }
 
-    possible_moves
+    for next in board.get_surrounding_slidable_tiles(position, path) {
+        path.push(next);
+        extend_slide_path(board, path, steps, destinations);
+        path.pop();
+    }
 }
 
-fn get_moves_for_spider(
-    position_cache: PositionCache,
-    start_position: HexCoordinate,
+/// Returns every position the pillbug at `pillbug` may carry the tile at `target` to, by lifting
+/// it on top of itself and setting it down on an empty space next to it. Whether the tile may
+/// be moved this turn at all is up to the caller, only `pinned` tiles are ruled out here.
+pub fn get_moves_for_pillbug_ability(
+    board: &Board,
+    pillbug: HexCoordinate,
+    target: HexCoordinate,
+    pinned: &HashSet<HexCoordinate>,
 ) -> Vec<HexCoordinate> {
-    let mut possible_moves = position_cache.get_surrounding_slidable_tiles(start_position, &vec![]);
-
-    let mut ignore = possible_moves.clone();
-    ignore.push(start_position);
-
-    for _ in 0..2 {
-        let mut new_moves = vec![];
-
-        for existing_move in &possible_moves {
-            for new_move in position_cache.get_surrounding_slidable_tiles(*existing_move, &ignore) {
-                new_moves.push(new_move);
-            }
-        }
-
-        if new_moves.len() == 0 {
-            break;
-        }
+    //only unstacked tiles can be carried
+    if board.height(&target) != 1 || pinned.contains(&target) {
+        return vec![];
+    }
 
-        possible_moves = new_moves.clone();
-        for new_move in &new_moves {
-            ignore.push(*new_move);
-        }
+    let board_without_target = board.lifted(target);
+    if is_blocked_by_gate(&board_without_target, target, pillbug, 1) {
+        return vec![];
     }
 
-    possible_moves
+    ALL_DIRECTIONS
+        .map(|dir| pillbug.get_relative(dir))
+        .into_iter()
+        .filter(|destination| {
+            *destination != target
+                && !board_without_target.contains(destination)
+                && !is_blocked_by_gate(&board_without_target, pillbug, *destination, 1)
+        })
+        .collect()
 }
 
-fn get_moves_for_new_piece(
-    position_cache: Res<PositionCache>,
+/// Whether a tile moving between the neighbouring positions `from` and `to`, `height` tiles
+/// above the ground, has to squeeze between two stacks that are both higher than that.
+pub(crate) fn is_blocked_by_gate(
+    board: &LiftedBoard,
+    from: HexCoordinate,
+    to: HexCoordinate,
+    height: usize,
+) -> bool {
+    let Some(direction) = ALL_DIRECTIONS
+        .into_iter()
+        .find(|direction| from.get_relative(direction) == to)
+    else {
+        return false;
+    };
+
+    direction
+        .get_adjacent_directions()
+        .iter()
+        .all(|side| board.height(&from.get_relative(side)) > height)
+}
+
+pub fn get_moves_for_new_piece(
+    board: &Board,
     current_player: Player,
     may_touch_other_player: bool,
 ) -> Vec<HexCoordinate> {
     let mut valid_moves = vec![];
 
-    //  spawn placement markers
     let mut already_checked = HashSet::new();
-    for (position, _) in &position_cache.0 {
+    for position in board.positions() {
         for position_to_check in ALL_DIRECTIONS.map(|x| position.get_relative(x)) {
             if already_checked.contains(&position_to_check) {
                 continue;
@@ -218,22 +295,16 @@ fn get_moves_for_new_piece(
 
             already_checked.insert(position_to_check);
 
-            if position_cache.0.contains_key(&position_to_check) {
+            if board.contains(&position_to_check) {
                 continue;
             }
 
             if !may_touch_other_player {
-                let mut touched_other_player = false;
-                for surrounding in ALL_DIRECTIONS.map(|x| position_to_check.get_relative(x)) {
-                    match position_cache.0.get(&surrounding) {
-                        None => {}
-                        Some(entry) => {
-                            if entry.player != current_player {
-                                touched_other_player = true;
-                            }
-                        }
-                    }
-                }
+                let touched_other_player = ALL_DIRECTIONS
+                    .map(|x| position_to_check.get_relative(x))
+                    .iter()
+                    .filter_map(|surrounding| board.get_top(surrounding))
+                    .any(|tile| tile.player != current_player);
 
                 if touched_other_player {
                     continue;
@@ -247,28 +318,94 @@ fn get_moves_for_new_piece(
     valid_moves
 }
 
-fn check_moving_piece_allowed(position_cache: &PositionCache) -> bool {
-    let mut checked_tiles: HashSet<HexCoordinate> = HashSet::new();
-    let mut open_list: Vec<HexCoordinate> = vec![];
-    let mut connected_tiles = vec![];
+/// The positions of all tiles that can't leave the ground without splitting the hive, found in a
+/// single pass over it instead of checking every tile on its own. These are the articulation
+/// points of the graph of neighbouring stacks, except for stacks with more than one tile, because
+/// their position stays occupied when the top tile leaves.
+///
+/// [`GameState::pinned_positions`](crate::game_state::GameState::pinned_positions) keeps the
+/// result for every position of a game, so each of them is only searched once.
+pub fn get_pinned_positions(board: &Board) -> HashSet<HexCoordinate> {
+    let mut search = ArticulationSearch {
+        board,
+        discovered: HashMap::new(),
+        pinned: HashSet::new(),
+    };
+    if let Some(root) = board.positions().next() {
+        search.visit(*root, None);
+    }
+    search.pinned
+}
 
-    let all_positions: Vec<_> = position_cache.0.keys().collect();
-    if all_positions.len() > 0 {
-        open_list.push(all_positions[0].clone());
+/// Tarjan's depth-first search for articulation points.
+struct ArticulationSearch<'a> {
+    board: &'a Board,
+    /// When each position was reached for the first time, counting from 0.
+    discovered: HashMap<HexCoordinate, usize>,
+    pinned: HashSet<HexCoordinate>,
+}
 
-        loop {
-            if open_list.len() == 0 {
-                break;
+impl ArticulationSearch<'_> {
+    /// Searches the hive from `position`, which was reached from `parent`. Returns the earliest
+    /// discovered position that `position` or anything found from it is next to, apart from
+    /// `parent`. If a child can't reach back to before `position` this way, taking `position`
+    /// away cuts it off.
+    fn visit(&mut self, position: HexCoordinate, parent: Option<HexCoordinate>) -> usize {
+        let discovered = self.discovered.len();
+        self.discovered.insert(position, discovered);
+
+        let mut earliest = discovered;
+        let mut children = 0;
+        for neighbour in ALL_DIRECTIONS.map(|dir| position.get_relative(dir)) {
+            if !self.board.contains(&neighbour) || Some(neighbour) == parent {
+                continue;
             }
 
-            let position = open_list.pop().unwrap();
+            match self.discovered.get(&neighbour) {
+                Some(neighbour_discovered) => earliest = earliest.min(*neighbour_discovered),
+                None => {
+                    children += 1;
+                    let child_earliest = self.visit(neighbour, Some(position));
+                    earliest = earliest.min(child_earliest);
+                    if parent.is_some() && child_earliest >= discovered {
+                        self.pin(position);
+                    }
+                }
+            }
+        }
 
+        //the start of the search only holds the hive together if it was entered more than once
+        if parent.is_none() && children > 1 {
+            self.pin(position);
+        }
+        earliest
+    }
+
+    fn pin(&mut self, position: HexCoordinate) {
+        if self.board.height(&position) == 1 {
+            self.pinned.insert(position);
+        }
+    }
+}
+
+/// Whether all tiles on `board` are connected. Checking the board without a tile tells if the
+/// tile may move, [`get_pinned_positions`] does that for all tiles at once.
+pub fn check_moving_piece_allowed(board: &Board) -> bool {
+    let mut checked_tiles: HashSet<HexCoordinate> = HashSet::new();
+    let mut open_list: Vec<HexCoordinate> = vec![];
+    let mut connected_tiles = vec![];
+
+    let all_positions: Vec<_> = board.positions().collect();
+    if !all_positions.is_empty() {
+        open_list.push(*all_positions[0]);
+
+        while let Some(position) = open_list.pop() {
             if checked_tiles.contains(&position) {
                 continue;
             }
             checked_tiles.insert(position);
 
-            if !position_cache.0.contains_key(&position) {
+            if !board.contains(&position) {
                 continue;
             }
 
@@ -284,8 +421,252 @@ fn check_moving_piece_allowed(position_cache: &PositionCache) -> bool {
         }
     }
 
-    if connected_tiles.len() == all_positions.len() {
-        return true;
+    connected_tiles.len() == all_positions.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::enums::InsectType::{
+        Ant, Beetle, Grasshopper, Ladybug, Mosquito, Pillbug, Queen,
+    };
+    use crate::game_state::{GameState, Move, Tile};
+    use crate::hex_coordinate::HexDirection::{DownRight, Left, Right, UpRight};
+    use crate::notation;
+    use crate::notation::{format_move, play_moves};
+
+    fn tile(insect: InsectType) -> Tile {
+        Tile {
+            player: Player::Player1,
+            insect,
+            ordinal: 1,
+        }
+    }
+
+    /// The moves of the top tile at `position`, after it was replaced by `insect`.
+    fn moves_as(
+        board: &Board,
+        position: HexCoordinate,
+        insect: InsectType,
+    ) -> HashSet<HexCoordinate> {
+        let mut board = board.clone();
+        board.pop(&position);
+        board.push(position, tile(insect));
+        get_moves_for_tile(&board, position).into_iter().collect()
+    }
+
+    #[test]
+    fn mosquito_copies_touching_insects() {
+        let mosquito = HexCoordinate::origin();
+
+        let mut board = Board::default();
+        board.push(mosquito, tile(Mosquito));
+        board.push(mosquito.get_relative(&Right), tile(Grasshopper));
+        board.push(mosquito.get_relative(&UpRight), tile(Queen));
+
+        let grasshopper = moves_as(&board, mosquito, Grasshopper);
+        let queen = moves_as(&board, mosquito, Queen);
+        let expected: HashSet<_> = grasshopper.union(&queen).copied().collect();
+        assert!(expected.len() > grasshopper.len().max(queen.len()));
+        assert_eq!(moves_as(&board, mosquito, Mosquito), expected);
+    }
+
+    #[test]
+    fn mosquito_on_top_moves_as_a_beetle() {
+        let mosquito = HexCoordinate::origin();
+
+        let mut board = Board::default();
+        board.push(mosquito, tile(Queen));
+        board.push(mosquito, tile(Mosquito));
+        board.push(mosquito.get_relative(&Right), tile(Grasshopper));
+
+        assert_eq!(
+            moves_as(&board, mosquito, Mosquito),
+            moves_as(&board, mosquito, Beetle)
+        );
+    }
+
+    #[test]
+    fn mosquito_cannot_copy_a_mosquito() {
+        let mosquito = HexCoordinate::origin();
+
+        let mut board = Board::default();
+        board.push(mosquito, tile(Mosquito));
+        board.push(mosquito.get_relative(&Right), tile(Mosquito));
+        board.push(mosquito.get_relative(&Left), tile(Mosquito));
+
+        assert!(get_moves_for_tile(&board, mosquito.get_relative(&Right)).is_empty());
+    }
+
+    #[test]
+    fn pillbug_cannot_move_tiles_through_gates() {
+        let pillbug = HexCoordinate::origin();
+        let target = pillbug.get_relative(&Right);
+
+        let mut board = Board::default();
+        board.push(pillbug, tile(Pillbug));
+        board.push(target, tile(Ant));
+        //the two positions next to both the pillbug and the target
+        for side in [UpRight, DownRight] {
+            board.push(pillbug.get_relative(&side), tile(Ant));
+        }
+        let carried = |board: &Board| {
+            get_moves_for_pillbug_ability(board, pillbug, target, &get_pinned_positions(board))
+        };
+        assert_eq!(carried(&board).len(), 3);
+
+        board.push(pillbug.get_relative(&UpRight), tile(Beetle));
+        assert_eq!(carried(&board).len(), 3);
+
+        board.push(pillbug.get_relative(&DownRight), tile(Beetle));
+        assert!(carried(&board).is_empty());
+    }
+
+    #[test]
+    fn ladybug_walks_two_steps_on_top_then_one_down() {
+        let ladybug = HexCoordinate::origin();
+        let first = ladybug.get_relative(&Right);
+        let second = first.get_relative(&Right);
+
+        let mut board = Board::default();
+        board.push(ladybug, tile(Ladybug));
+        board.push(first, tile(Ant));
+        board.push(second, tile(Ant));
+
+        //it can't stop on top of the hive or get down right after the first step
+        let expected: HashSet<_> = ALL_DIRECTIONS
+            .map(|dir| second.get_relative(dir))
+            .into_iter()
+            .filter(|position| !board.contains(position))
+            .collect();
+        assert_eq!(expected.len(), 5);
+        assert_eq!(moves_as(&board, ladybug, Ladybug), expected);
+    }
+
+    #[test]
+    fn ladybug_needs_two_tiles_to_walk_over() {
+        let ladybug = HexCoordinate::origin();
+
+        let mut board = Board::default();
+        board.push(ladybug, tile(Ladybug));
+        board.push(ladybug.get_relative(&Right), tile(Queen));
+
+        assert!(get_moves_for_tile(&board, ladybug).is_empty());
+    }
+
+    #[test]
+    fn beetle_on_top_cannot_pass_between_higher_stacks() {
+        let beetle = HexCoordinate::origin();
+        let target = beetle.get_relative(&Right);
+
+        let mut board = Board::default();
+        board.push(beetle, tile(Ant));
+        board.push(beetle, tile(Beetle));
+        //the two positions next to both the beetle and the target are one tile higher
+        for side in [UpRight, DownRight] {
+            board.push(beetle.get_relative(&side), tile(Ant));
+            board.push(beetle.get_relative(&side), tile(Beetle));
+        }
+        assert!(!get_moves_for_tile(&board, beetle).contains(&target));
+        assert!(get_moves_for_tile(&board, beetle).contains(&beetle.get_relative(&UpRight)));
+
+        //a stack as high as the beetle doesn't block it anymore
+        board.pop(&beetle.get_relative(&UpRight));
+        assert!(get_moves_for_tile(&board, beetle).contains(&target));
+    }
+
+    #[test]
+    fn beetle_cannot_climb_through_a_gate() {
+        let beetle = HexCoordinate::origin();
+        let target = beetle.get_relative(&Right);
+
+        let mut board = Board::default();
+        board.push(beetle, tile(Beetle));
+        board.push(target, tile(Ant));
+        for side in [UpRight, DownRight] {
+            board.push(beetle.get_relative(&side), tile(Ant));
+            board.push(beetle.get_relative(&side), tile(Beetle));
+        }
+        assert!(!get_moves_for_tile(&board, beetle).contains(&target));
+
+        board.pop(&beetle.get_relative(&DownRight));
+        assert!(get_moves_for_tile(&board, beetle).contains(&target));
+    }
+
+    /// Positions where sliding tiles are easy to get wrong, with the tile to move and its moves.
+    const SLIDING_CORPUS: &[(&str, &str, &[&str])] = &[
+        //a spider at the end of a line reaches both sides of the far end
+        ("wQ;bQ wQ-;wS1 -wQ;bS1 bQ-", "bS1", &["bS1 /wQ", "bS1 \\wQ"]),
+        //paths that cross each other must not hide destinations from one another
+        (
+            "wA1;bA1 /wA1;wS1 \\wA1;bP -bA1;wQ wA1/;bQ \\bP;wP \\wQ;bA2 /bP;wB1 -wP",
+            "wS1",
+            &["wS1 -bQ", "wS1 -wB1", "wS1 \\bQ", "wS1 \\wB1"],
+        ),
+        (
+            "wS1;bP wS1-;wQ -wS1;bG1 bP/;wG1 \\wQ;bQ \\bG1;wB1 wG1/;bB1 bG1/;wB2 /wS1;bS1 \\bQ",
+            "bS1",
+            &["bS1 /bQ", "bS1 \\wB1", "bS1 \\wS1", "bS1 bB1-"],
+        ),
+        //a spider that gets stuck after one or two steps can't stop early
+        (
+            "wA1;bA1 /wA1;wS1 \\wA1;bP -bA1;wQ wA1/;bQ \\bP;wP \\wQ;bA2 /bP;wB1 -wP;bS1 \\bQ",
+            "wS1",
+            &[],
+        ),
+        (
+            "wG1;bG1 wG1/;wQ /wG1;bG2 \\bG1;wS1 wQ-;bQ -bG2;wS2 -wG1;bS1 bG1-;wS3 /wS2;bA1 -bQ;wA1 -wS2",
+            "wS2",
+            &[],
+        ),
+        //the hole next to wQ is surrounded by five tiles, so the ant can't slide into it
+        (
+            "wQ;bQ wQ-;wA1 -wQ;bA1 bQ-;wA2 /wA1;bA2 bA1\\;wA3 wA2\\;bA2 /bQ",
+            "bA1",
+            &[
+                "bA1 -wA1", "bA1 -wA2", "bA1 /bA2", "bA1 /wA2", "bA1 /wA3", "bA1 \\bQ",
+                "bA1 \\wA1", "bA1 \\wQ", "bA1 bA2-", "bA1 bA2\\", "bA1 bQ/", "bA1 wA3\\",
+            ],
+        ),
+    ];
+
+    #[test]
+    fn sliding_corpus() {
+        for (moves, name, expected) in SLIDING_CORPUS {
+            let mut game_state = GameState::new();
+            play_moves(&mut game_state, moves).unwrap();
+            let from = game_state.find_tile(&notation::tile(name)).unwrap();
+
+            let mut actual: Vec<_> = get_moves_for_tile(game_state.board(), from)
+                .into_iter()
+                .map(|to| format_move(&game_state, &Move::Move { from, to }))
+                .collect();
+            actual.sort();
+            assert_eq!(actual, *expected, "{moves}");
+        }
+    }
+
+    #[test]
+    fn pinned_positions_split_the_hive() {
+        for moves in [
+            "wQ;bQ wQ-;wB1 -wQ;bB1 bQ-;wB1 wQ",
+            "wQ;bQ wQ-;wA1 -wQ;bA1 bQ-;wA2 /wA1;bA2 bA1\\;wA3 wA2\\;bA2 /bQ",
+            "wG1;bG1 wG1/;wQ /wG1;bG2 \\bG1;wS1 wQ-;bQ -bG2;wS2 -wG1;bS1 bG1-;wS3 /wS2;bA1 -bQ",
+        ] {
+            let mut game_state = GameState::new();
+            play_moves(&mut game_state, moves).unwrap();
+            let board = game_state.board();
+
+            let expected: HashSet<_> = board
+                .positions()
+                .filter(|position| {
+                    board.height(position) == 1
+                        && !check_moving_piece_allowed(&board.get_without(position))
+                })
+                .copied()
+                .collect();
+            assert!(!expected.is_empty());
+            assert_eq!(get_pinned_positions(board), expected, "{moves}");
+        }
     }
-    false
 }
@@ -0,0 +1,144 @@
+//! Stores the complete [`GameState`] as RON, including the move history so undo keeps
+//! working after a game was loaded. Games recorded in move notation can be loaded as well.
+
+use crate::game_state::{GameState, IllegalMove};
+use crate::notation::{play_moves, NotationError};
+use crate::rule_set::RuleSet;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum SaveGameError {
+    Io(std::io::Error),
+    Serialize(ron::Error),
+    Deserialize(ron::error::SpannedError),
+    Notation(NotationError),
+    IllegalMove(IllegalMove),
+}
+
+impl fmt::Display for SaveGameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveGameError::Io(error) => write!(f, "{error}"),
+            SaveGameError::Serialize(error) => write!(f, "could not write game: {error}"),
+            SaveGameError::Deserialize(error) => write!(f, "could not read game: {error}"),
+            SaveGameError::Notation(error) => write!(f, "could not read moves: {error}"),
+            SaveGameError::IllegalMove(error) => write!(f, "could not replay game: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for SaveGameError {}
+
+pub fn save_to_string(game_state: &GameState) -> Result<String, SaveGameError> {
+    ron::ser::to_string_pretty(game_state, ron::ser::PrettyConfig::default())
+        .map_err(SaveGameError::Serialize)
+}
+
+pub fn load_from_str(s: &str) -> Result<GameState, SaveGameError> {
+    let game_state: GameState = ron::from_str(s).map_err(SaveGameError::Deserialize)?;
+    game_state.replayed().map_err(SaveGameError::IllegalMove)
+}
+
+pub fn save_to_file(game_state: &GameState, path: &Path) -> Result<(), SaveGameError> {
+    std::fs::write(path, save_to_string(game_state)?).map_err(SaveGameError::Io)
+}
+
+/// Reads the `;` separated moves of a game record. A leading UHP header such as
+/// `Base+M;InProgress;White[3];` selects the rules, otherwise the default rules are used.
+pub fn load_from_moves(s: &str) -> Result<GameState, SaveGameError> {
+    let s = s.trim();
+    let header = s.split_once(';').and_then(|(game_type, game_string)| {
+        let rule_set = RuleSet::from_game_type(game_type)?;
+        Some((rule_set, game_string.splitn(3, ';').nth(2).unwrap_or("")))
+    });
+    let (rule_set, moves) = header.unwrap_or_else(|| (RuleSet::default(), s));
+
+    let mut game_state = GameState::with_rules(rule_set);
+    play_moves(&mut game_state, moves).map_err(SaveGameError::Notation)?;
+    Ok(game_state)
+}
+
+/// Loads a `.ron` file written by [`save_to_file`], any other file is read as a game record.
+pub fn load_from_file(path: &Path) -> Result<GameState, SaveGameError> {
+    let content = std::fs::read_to_string(path).map_err(SaveGameError::Io)?;
+
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("ron") => load_from_str(&content),
+        _ => load_from_moves(&content),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::enums::Player;
+    use crate::notation::{format_game, play_moves, tile};
+
+    #[test]
+    fn saved_games_load_with_history() {
+        let mut game_state = GameState::new();
+        play_moves(&mut game_state, "wQ;bQ wQ-;wB1 -wQ;bB1 bQ-;wB1 wQ").unwrap();
+
+        let mut loaded = load_from_str(&save_to_string(&game_state).unwrap()).unwrap();
+        assert_eq!(loaded.board(), game_state.board());
+        assert_eq!(loaded.current_player(), game_state.current_player());
+        assert_eq!(format_game(&loaded), format_game(&game_state));
+        assert_eq!(loaded.zobrist_hash(), game_state.zobrist_hash());
+
+        let covered = game_state.find_tile(&tile("wQ")).unwrap();
+        assert_eq!(loaded.board().height(&covered), 2);
+
+        loaded.undo();
+        game_state.undo();
+        assert_eq!(loaded.board(), game_state.board());
+        assert_eq!(loaded.zobrist_hash(), game_state.zobrist_hash());
+        assert_eq!(
+            loaded.inventory(loaded.current_player()).pieces,
+            game_state.inventory(game_state.current_player()).pieces
+        );
+    }
+
+    #[test]
+    fn saved_games_keep_their_result() {
+        let mut game_state = GameState::new();
+        play_moves(&mut game_state, "wQ;bQ wQ-").unwrap();
+        game_state.resign(Player::Player1);
+
+        let loaded = load_from_str(&save_to_string(&game_state).unwrap()).unwrap();
+        assert_eq!(loaded.result(), game_state.result());
+    }
+
+    #[test]
+    fn game_records_load() {
+        let moves = "wB1;bB1 wB1-;wQ -wB1;bQ bB1-";
+        let game_state = load_from_moves(moves).unwrap();
+        assert_eq!(format_game(&game_state), moves);
+
+        let uhp = load_from_moves(&format!("Base;InProgress;White[3];{moves}")).unwrap();
+        assert_eq!(uhp.board(), game_state.board());
+        assert_eq!(uhp.rules(), &RuleSet::base_game());
+
+        assert!(load_from_moves("wQ;bQ").is_err());
+    }
+
+    #[test]
+    fn corrupted_saves_fail_to_load() {
+        let game_state = load_from_moves("wQ;bQ wQ-;wB1 -wQ").unwrap();
+        let saved = save_to_string(&game_state).unwrap();
+
+        //the moves are replayed, so the saved inventory indices are not trusted
+        let wrong_index = saved.replace("inventory_index: 4", "inventory_index: 99");
+        assert_ne!(wrong_index, saved);
+        let loaded = load_from_str(&wrong_index).unwrap();
+        assert_eq!(loaded.board(), game_state.board());
+
+        //places the black queen away from the hive
+        let detached = saved.replacen("                    x: 1,", "                    x: 5,", 1);
+        assert_ne!(detached, saved);
+        assert!(matches!(
+            load_from_str(&detached),
+            Err(SaveGameError::IllegalMove(_))
+        ));
+    }
+}
@@ -1,16 +1,251 @@
-use crate::data::components::{CurrentPlayer, GameAssets, GameResultResource};
-use crate::data::enums::{GameResult, Player};
+use crate::game_end::{DrawOffer, GameEndRequest};
+use crate::history::HistoryRequest;
+use crate::new_game::NewGameRequest;
+use crate::opponent::{Controller, PlayerControllers};
+use crate::persistence::SaveGameRequest;
+use crate::replay::{Replay, ReplayRequest};
 use bevy::prelude::Commands;
 use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use hive_bevy::data::components::{CurrentPlayer, GameAssets, GameResultResource};
+use hive_bevy::data::enums::{DrawReason, GameResult, Player, WinReason};
+use hive_bevy::game_state::{GameState, Move};
+use hive_bevy::rule_set::{RuleSet, EXPANSIONS};
 
 #[derive(Component)]
 pub struct UIStatusText {}
 
+#[derive(Component)]
+pub struct UIHistoryButton(HistoryRequest);
+
+pub fn s_handle_history_buttons(
+    q_buttons: Query<(&Interaction, &UIHistoryButton), Changed<Interaction>>,
+    mut history_requests: EventWriter<HistoryRequest>,
+) {
+    for (interaction, button) in &q_buttons {
+        if *interaction == Interaction::Pressed {
+            history_requests.send(button.0);
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct UIGameEndButton(GameEndRequest);
+
+pub fn s_handle_game_end_buttons(
+    q_buttons: Query<(&Interaction, &UIGameEndButton), Changed<Interaction>>,
+    mut game_end_requests: EventWriter<GameEndRequest>,
+) {
+    for (interaction, button) in &q_buttons {
+        if *interaction == Interaction::Pressed {
+            game_end_requests.send(button.0);
+        }
+    }
+}
+
+pub fn s_update_game_end_buttons(
+    draw_offer: Res<DrawOffer>,
+    game_state: Res<GameState>,
+    q_buttons: Query<(&UIGameEndButton, &Children)>,
+    mut q_text: Query<&mut Text>,
+) {
+    if !draw_offer.is_changed() && !game_state.is_changed() {
+        return;
+    }
+
+    let label = if draw_offer.is_open(&game_state) {
+        "Accept draw"
+    } else if draw_offer.made_by(&game_state, game_state.current_player()) {
+        "Draw offered"
+    } else {
+        "Offer draw"
+    };
+
+    for (button, children) in &q_buttons {
+        let GameEndRequest::OfferDraw = button.0 else {
+            continue;
+        };
+
+        for child in children {
+            if let Ok(mut text) = q_text.get_mut(*child) {
+                text.sections[0].value = label.to_string();
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct UISaveGameButton(SaveGameRequest);
+
+pub fn s_handle_save_game_buttons(
+    q_buttons: Query<(&Interaction, &UISaveGameButton), Changed<Interaction>>,
+    mut save_game_requests: EventWriter<SaveGameRequest>,
+) {
+    for (interaction, button) in &q_buttons {
+        if *interaction == Interaction::Pressed {
+            save_game_requests.send(button.0);
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct UIReplayButton(ReplayRequest);
+
+#[derive(Component)]
+pub struct UIReplayBar {}
+
+#[derive(Component)]
+pub struct UIReplayTimeline {}
+
+#[derive(Component)]
+pub struct UIReplayProgress {}
+
+pub fn s_handle_replay_buttons(
+    q_buttons: Query<(&Interaction, &UIReplayButton), Changed<Interaction>>,
+    mut replay_requests: EventWriter<ReplayRequest>,
+) {
+    for (interaction, button) in &q_buttons {
+        if *interaction == Interaction::Pressed {
+            replay_requests.send(button.0);
+        }
+    }
+}
+
+pub fn s_handle_replay_timeline(
+    q_timeline: Query<
+        (&Interaction, &RelativeCursorPosition),
+        (Changed<Interaction>, With<UIReplayTimeline>),
+    >,
+    replay: Option<Res<Replay>>,
+    mut replay_requests: EventWriter<ReplayRequest>,
+) {
+    let Some(replay) = replay else {
+        return;
+    };
+
+    for (interaction, cursor) in &q_timeline {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if let Some(position) = cursor.normalized {
+            let ply = (position.x.clamp(0., 1.) * replay.len() as f32).round() as usize;
+            replay_requests.send(ReplayRequest::JumpTo(ply));
+        }
+    }
+}
+
+pub fn s_update_replay_bar(
+    replay: Option<Res<Replay>>,
+    mut q_bar: Query<&mut Visibility, With<UIReplayBar>>,
+    mut q_progress: Query<&mut Style, With<UIReplayProgress>>,
+) {
+    for mut visibility in &mut q_bar {
+        *visibility = match replay {
+            Some(_) => Visibility::Visible,
+            None => Visibility::Hidden,
+        };
+    }
+
+    if let Some(replay) = replay {
+        let progress = match replay.len() {
+            0 => 100.,
+            len => replay.ply as f32 / len as f32 * 100.,
+        };
+        for mut style in &mut q_progress {
+            style.width = Val::Percent(progress);
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct UIControllerButton(Player);
+
+pub fn s_handle_controller_buttons(
+    q_buttons: Query<(&Interaction, &UIControllerButton), Changed<Interaction>>,
+    mut controllers: ResMut<PlayerControllers>,
+) {
+    for (interaction, button) in &q_buttons {
+        if *interaction == Interaction::Pressed {
+            let controller = controllers.get_mut(button.0);
+            *controller = controller.next();
+        }
+    }
+}
+
+pub fn s_update_controller_buttons(
+    controllers: Res<PlayerControllers>,
+    q_buttons: Query<(&UIControllerButton, &Children)>,
+    mut q_text: Query<&mut Text>,
+) {
+    if !controllers.is_changed() {
+        return;
+    }
+
+    for (button, children) in &q_buttons {
+        let controller = match controllers.get(button.0) {
+            Controller::Human => "Human".to_string(),
+            Controller::Computer(bot) => bot.to_string(),
+        };
+        let player = match button.0 {
+            Player::Player1 => "Player1",
+            Player::Player2 => "Player2",
+        };
+
+        for child in children {
+            if let Ok(mut text) = q_text.get_mut(*child) {
+                text.sections[0].value = format!("{player}: {controller}");
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct UINewGameButton(NewGameRequest);
+
+pub fn s_handle_new_game_buttons(
+    q_buttons: Query<(&Interaction, &UINewGameButton), Changed<Interaction>>,
+    mut new_game_requests: EventWriter<NewGameRequest>,
+) {
+    for (interaction, button) in &q_buttons {
+        if *interaction == Interaction::Pressed {
+            new_game_requests.send(button.0);
+        }
+    }
+}
+
+pub fn s_update_new_game_buttons(
+    rule_set: Res<RuleSet>,
+    q_buttons: Query<(&UINewGameButton, &Children)>,
+    mut q_text: Query<&mut Text>,
+) {
+    if !rule_set.is_changed() {
+        return;
+    }
+
+    for (button, children) in &q_buttons {
+        let NewGameRequest::ToggleExpansion(insect) = button.0 else {
+            continue;
+        };
+        let state = match rule_set.count(insect) {
+            0 => "Off",
+            _ => "On",
+        };
+
+        for child in children {
+            if let Ok(mut text) = q_text.get_mut(*child) {
+                text.sections[0].value = format!("{insect:?}: {state}");
+            }
+        }
+    }
+}
+
 pub fn s_update_ui_for_round(
     mut q_text: Query<&mut Text, With<UIStatusText>>,
     game_assets: Res<GameAssets>,
     current_player: Res<CurrentPlayer>,
     state: Res<GameResultResource>,
+    game_state: Res<GameState>,
+    replay: Option<Res<Replay>>,
 ) {
     let text: &mut bevy::prelude::Text = &mut q_text.single_mut();
     let color;
@@ -19,37 +254,63 @@ pub fn s_update_ui_for_round(
 
     match &state.result {
         None => {
-            string = match current_player.player {
+            let player = match current_player.player {
                 Player::Player1 => "Player1".to_string(),
                 Player::Player2 => "Player2".to_string(),
             };
+            //passes only happen when there is nothing else to do, so they are pointed out
+            string = match game_state.history().last() {
+                Some(Move::Pass) => format!(
+                    "{player} ({:?} had no legal move and passed)",
+                    current_player.player.other()
+                ),
+                _ => player,
+            };
             color = game_assets.get_color_for_player(current_player.player);
         }
         Some(game_result) => match game_result {
-            GameResult::Draw => {
+            GameResult::Draw(reason) => {
                 color = Color::LinearRgba(LinearRgba {
                     red: 0.5,
                     green: 0.5,
                     blue: 0.5,
                     alpha: 1.0,
                 });
-                string = "Draw!".to_string();
+                string = match reason {
+                    DrawReason::QueensSurrounded => "Draw, both queens are surrounded!",
+                    DrawReason::NoLegalMoves => "Draw, nobody can move!",
+                    DrawReason::Agreement => "Draw by agreement!",
+                    DrawReason::Repetition => "Draw by repetition!",
+                    DrawReason::MoveLimit => "Draw, the move limit was reached!",
+                }
+                .to_string();
             }
-            GameResult::PlayerWon(player_that_won) => {
-                string = match player_that_won {
-                    Player::Player1 => "Player1 won!!".to_string(),
-                    Player::Player2 => "Player2 won!!".to_string(),
+            GameResult::PlayerWon(player_that_won, reason) => {
+                let player = match player_that_won {
+                    Player::Player1 => "Player1",
+                    Player::Player2 => "Player2",
+                };
+                string = match reason {
+                    WinReason::QueenSurrounded => format!("{player} won!!"),
+                    WinReason::Resignation => format!("{player} won by resignation!!"),
                 };
                 color = game_assets.get_color_for_player(current_player.player);
             }
         },
     }
 
+    let string = match replay {
+        Some(replay) => format!("Replay {}/{} - {string}", replay.ply, replay.len()),
+        None => string,
+    };
+
     text.sections[0].style.color = color;
     text.sections[0].value = string;
 }
 
 pub fn s_setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("FiraMono-Medium.ttf");
+
     commands
         .spawn(NodeBundle {
             style: Style {
@@ -65,14 +326,175 @@ pub fn s_setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                 TextBundle::from_section(
                     "Text Example",
                     TextStyle {
-                        font: asset_server.load("FiraMono-Medium.ttf"),
+                        font: font.clone(),
                         font_size: 30.0,
-                        color: Color::srgb(1., 0., 0.).into(),
-                        ..default()
+                        color: Color::srgb(1., 0., 0.),
                     },
                 ),
                 Label,
                 UIStatusText {},
             ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::FlexStart,
+                        column_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    spawn_button(parent, &font, "Undo", UIHistoryButton(HistoryRequest::Undo));
+                    spawn_button(parent, &font, "Redo", UIHistoryButton(HistoryRequest::Redo));
+                    spawn_button(
+                        parent,
+                        &font,
+                        "Save",
+                        UISaveGameButton(SaveGameRequest::Save),
+                    );
+                    spawn_button(
+                        parent,
+                        &font,
+                        "Load",
+                        UISaveGameButton(SaveGameRequest::Load),
+                    );
+                    spawn_button(
+                        parent,
+                        &font,
+                        "Replay",
+                        UIReplayButton(ReplayRequest::Toggle),
+                    );
+                    spawn_button(
+                        parent,
+                        &font,
+                        "Resign",
+                        UIGameEndButton(GameEndRequest::Resign),
+                    );
+                    spawn_button(
+                        parent,
+                        &font,
+                        "Offer draw",
+                        UIGameEndButton(GameEndRequest::OfferDraw),
+                    );
+                    spawn_button(
+                        parent,
+                        &font,
+                        "New game",
+                        UINewGameButton(NewGameRequest::Restart),
+                    );
+                    for insect in EXPANSIONS {
+                        //the label is set once the rule set is known
+                        spawn_button(
+                            parent,
+                            &font,
+                            "",
+                            UINewGameButton(NewGameRequest::ToggleExpansion(insect)),
+                        );
+                    }
+                    spawn_button(
+                        parent,
+                        &font,
+                        "Player1: Human",
+                        UIControllerButton(Player::Player1),
+                    );
+                    spawn_button(
+                        parent,
+                        &font,
+                        "Player2: Human",
+                        UIControllerButton(Player::Player2),
+                    );
+                });
+        });
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(10.0),
+                    right: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(10.0),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            UIReplayBar {},
+        ))
+        .with_children(|parent| {
+            for (label, request) in [
+                ("|<", ReplayRequest::First),
+                ("<", ReplayRequest::Back),
+                ("Play", ReplayRequest::ToggleAutoplay),
+                (">", ReplayRequest::Forward),
+                (">|", ReplayRequest::Last),
+                ("-", ReplayRequest::Slower),
+                ("+", ReplayRequest::Faster),
+            ] {
+                spawn_button(parent, &font, label, UIReplayButton(request));
+            }
+
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_grow: 1.0,
+                            height: Val::Px(20.0),
+                            ..default()
+                        },
+                        background_color: Color::srgb(0.2, 0.2, 0.2).into(),
+                        ..default()
+                    },
+                    Interaction::default(),
+                    RelativeCursorPosition::default(),
+                    UIReplayTimeline {},
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: Color::srgb(0.6, 0.6, 0.6).into(),
+                            ..default()
+                        },
+                        UIReplayProgress {},
+                    ));
+                });
+        });
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    marker: impl Component,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::all(Val::Px(5.0)),
+                    ..default()
+                },
+                background_color: Color::srgb(0.2, 0.2, 0.2).into(),
+                ..default()
+            },
+            marker,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ));
         });
 }
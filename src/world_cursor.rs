@@ -1,4 +1,3 @@
-use crate::data::components::MainCamera;
 use crate::world_cursor::PressState::*;
 use bevy::app::{App, Plugin, Update};
 use bevy::input::ButtonInput;
@@ -7,12 +6,15 @@ use bevy::prelude::{
     Camera, GlobalTransform, MouseButton, Query, Res, ResMut, Resource, Window, With,
 };
 use bevy::window::PrimaryWindow;
+use hive_bevy::data::components::MainCamera;
 
 /// We will store the world position of the mouse cursor here.
 #[derive(Resource, Default)]
 pub struct WorldCursor {
     pub(crate) position: Vec2,
     pub(crate) press_state: PressState,
+    /// While disabled, clicks are ignored and the press state stays released.
+    pub(crate) disabled: bool,
 }
 
 #[derive(Default, Debug)]
@@ -49,6 +51,11 @@ fn my_cursor_system(
         coord.position = world_position;
     }
 
+    if coord.disabled {
+        coord.press_state = Released;
+        return;
+    }
+
     match coord.press_state {
         Released => {
             if mouse.just_pressed(MouseButton::Left) {
@@ -0,0 +1,199 @@
+//! Zobrist hashing of positions. Every feature of a position, like a tile at some height of a
+//! stack, has a key that looks random, and a position hashes to the xor of the keys of all its
+//! features. A move only changes a few features, so [`GameState`] keeps its hash up to date by
+//! toggling their keys instead of hashing the whole position again.
+//!
+//! The hive has no bounds, so instead of a table of random numbers the keys come from packing
+//! each feature into the bits of a number and scrambling them with splitmix64. That keeps the
+//! keys the same on every platform and Rust release, and different features never share a key.
+
+use crate::data::enums::{InsectType, Player};
+use crate::game_state::{Board, GameState, Tile};
+use crate::hex_coordinate::HexCoordinate;
+
+enum Feature {
+    Tile(Tile, HexCoordinate, usize),
+    Hand(Player, InsectType, usize),
+    Turn,
+    Stunned(HexCoordinate),
+}
+
+impl Feature {
+    /// The kind of feature in the lowest 2 bits, then the player, the insect, the ordinal or
+    /// count, the position and the height. Every field fits as long as the hive stays within
+    /// 32768 tiles of the origin.
+    fn bits(&self) -> u64 {
+        let position_bits = |position: &HexCoordinate| {
+            let (x, y) = position.axial();
+            (x as u16 as u64) | (y as u16 as u64) << 16
+        };
+        let piece_bits = |player: Player, insect: InsectType, number: u64| {
+            (player as u64) << 2 | (insect as u64) << 3 | (number & 0xff) << 7
+        };
+
+        match self {
+            Feature::Tile(tile, position, height) => {
+                piece_bits(tile.player, tile.insect, tile.ordinal as u64)
+                    | position_bits(position) << 15
+                    | (*height as u64 & 0xff) << 47
+            }
+            Feature::Hand(player, insect, count) => 1 | piece_bits(*player, *insect, *count as u64),
+            Feature::Turn => 2,
+            Feature::Stunned(position) => 3 | position_bits(position) << 15,
+        }
+    }
+}
+
+/// The finalizer of splitmix64, which scrambles every bit of `z` into every bit of the result
+/// and never maps two numbers to the same one.
+fn splitmix64(z: u64) -> u64 {
+    let z = z.wrapping_add(0x9e3779b97f4a7c15);
+    let z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    let z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+fn key(feature: Feature) -> u64 {
+    splitmix64(feature.bits())
+}
+
+/// `tile` in the stack at `position`, with `height` tiles below it.
+pub fn tile_key(tile: &Tile, position: HexCoordinate, height: usize) -> u64 {
+    key(Feature::Tile(*tile, position, height))
+}
+
+/// `player` having `count` pieces of `insect` left to place.
+pub fn hand_key(player: Player, insect: InsectType, count: usize) -> u64 {
+    match count {
+        0 => 0,
+        _ => key(Feature::Hand(player, insect, count)),
+    }
+}
+
+/// Whose turn it is.
+pub fn turn_key(player: Player) -> u64 {
+    match player {
+        Player::Player1 => 0,
+        Player::Player2 => key(Feature::Turn),
+    }
+}
+
+/// The tile at `position` was moved last, so a pillbug can't move it.
+pub fn stunned_key(position: Option<HexCoordinate>) -> u64 {
+    position.map_or(0, |position| key(Feature::Stunned(position)))
+}
+
+/// Hashes the tiles, hands and turn of `game_state` from scratch, which is what
+/// [`GameState::zobrist_hash`] stays equal to.
+pub fn hash(game_state: &GameState) -> u64 {
+    board_key(game_state.board()) ^ hands_and_turn_key(game_state)
+}
+
+/// Like [`hash`], but equal for positions that only differ by where the hive is or by rotating
+/// or mirroring it, see [`Board::canonical`].
+pub fn canonical_hash(game_state: &GameState) -> u64 {
+    board_key(&game_state.board().canonical()) ^ hands_and_turn_key(game_state)
+}
+
+pub fn board_key(board: &Board) -> u64 {
+    let mut key = 0;
+    for (position, stack) in board.stacks() {
+        for (height, tile) in stack.enumerate() {
+            key ^= tile_key(&tile, *position, height);
+        }
+    }
+    key
+}
+
+fn hands_and_turn_key(game_state: &GameState) -> u64 {
+    let mut key = turn_key(game_state.current_player());
+
+    for player in [Player::Player1, Player::Player2] {
+        let pieces = &game_state.inventory(player).pieces;
+        for (index, insect) in pieces.iter().enumerate() {
+            //each insect is counted once, at its first piece
+            if !pieces[..index].contains(insect) {
+                let count = pieces.iter().filter(|i| *i == insect).count();
+                key ^= hand_key(player, *insect, count);
+            }
+        }
+    }
+
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notation::{play, tile};
+
+    #[test]
+    fn keys_stay_the_same() {
+        //saved hashes, e.g. in opening books, need the keys to never change
+        let queen = tile("wQ");
+        assert_eq!(
+            tile_key(&queen, HexCoordinate::origin(), 0),
+            4413419251928811825
+        );
+        assert_eq!(turn_key(Player::Player2), 10905525725756348110);
+    }
+
+    #[test]
+    fn move_orders_reaching_the_same_position_hash_the_same() {
+        let placed = play("wQ;bQ wQ-;wA1 -wQ;bA1 bQ-;wG1 /wQ;bG1 bQ/");
+        let swapped = play("wQ;bQ wQ-;wG1 /wQ;bA1 bQ-;wA1 -wQ;bG1 bQ/");
+        assert_eq!(placed.zobrist_hash(), swapped.zobrist_hash());
+
+        //the ant walks around the hive the other way
+        let walked = play("wQ;bQ wQ-;wA1 -wQ;bA1 bQ-;wA1 \\wQ;bG1 bQ/;wA1 wQ/;bG2 bG1/");
+        let around = play("wQ;bQ wQ-;wA1 -wQ;bA1 bQ-;wA1 wQ\\;bG1 bQ/;wA1 wQ/;bG2 bG1/");
+        assert_eq!(walked.zobrist_hash(), around.zobrist_hash());
+        assert_eq!(walked.zobrist_hash(), hash(&walked));
+    }
+
+    #[test]
+    fn hash_changes_with_the_turn_and_the_hands() {
+        let game_state = play("wQ;bQ wQ-;wA1 -wQ;bA1 bQ-");
+
+        let mut passed = game_state.clone();
+        passed.apply(crate::game_state::Move::Pass);
+        assert_ne!(passed.zobrist_hash(), game_state.zobrist_hash());
+
+        //the same tiles, but white placed its first grasshopper instead of an ant
+        let other_hand = play("wQ;bQ wQ-;wG1 -wQ;bA1 bQ-");
+        assert_ne!(other_hand.zobrist_hash(), game_state.zobrist_hash());
+    }
+
+    #[test]
+    fn moves_update_the_hash_like_hashing_from_scratch() {
+        let mut game_state = play("wQ;bQ wQ-;wB1 -wQ;bB1 bQ-;wB1 wQ;bP bB1-");
+        let before = game_state.zobrist_hash();
+        assert_eq!(before, hash(&game_state));
+
+        for mv in game_state.legal_moves() {
+            game_state.apply(mv);
+            assert_eq!(game_state.zobrist_hash(), hash(&game_state), "{mv:?}");
+            game_state.undo();
+            assert_eq!(game_state.zobrist_hash(), before);
+        }
+
+        assert_eq!(
+            game_state.initial().zobrist_hash(),
+            GameState::new().zobrist_hash()
+        );
+    }
+
+    #[test]
+    fn symmetric_positions_have_the_same_canonical_hash() {
+        let game_state = play("wQ;bQ wQ-;wA1 -wQ;bA1 bQ/");
+        let mirrored = play("wQ;bQ wQ-;wA1 -wQ;bA1 bQ\\");
+        let rotated = play("wQ;bQ wQ/;wA1 /wQ;bA1 \\bQ");
+        for other in [&mirrored, &rotated] {
+            assert_ne!(other.zobrist_hash(), game_state.zobrist_hash());
+            assert_eq!(canonical_hash(other), canonical_hash(&game_state));
+        }
+
+        let other_shape = play("wQ;bQ wQ-;wA1 -wQ;bA1 bQ-");
+        assert_ne!(canonical_hash(&other_shape), canonical_hash(&game_state));
+    }
+}